@@ -0,0 +1,438 @@
+//! Privacy-preserving request classification.
+//!
+//! Only the referrer host and a coarse user agent family are kept; never IP
+//! addresses or full user agent strings.
+
+use super::*;
+
+/// Host of the site itself, used to tell internal referrers apart.
+pub const SITE_HOST: &str = "hachha.dev";
+
+/// Known feed readers and aggregators, matched case-insensitively.
+const FEED_READERS: &[&str] = &[
+    "Feedly",
+    "Inoreader",
+    "NewsBlur",
+    "Feedbin",
+    "Miniflux",
+    "FreshRSS",
+    "NetNewsWire",
+    "Tiny Tiny RSS",
+    "The Old Reader",
+    "Feedspot",
+    "Feedfetcher-Google",
+    "Bloglovin",
+    "Newsboat",
+    "Liferea",
+    "Akregator",
+    "QuiteRSS",
+    "Reeder",
+    "ReadKit",
+    "selfoss",
+    "Nextcloud-News",
+    "Thunderbird",
+    "NewsGator",
+    "Feeder",
+    "Slipstream",
+];
+
+/// Generic markers of feed readers, used when no known reader matches.
+const FEED_READER_MARKERS: &[&str] = &["rss", "atom", "feed", "subscriber"];
+
+/// Generic markers of automated clients.
+const BOT_MARKERS: &[&str] = &[
+    "bot",
+    "crawler",
+    "spider",
+    "curl",
+    "wget",
+    "python",
+    "go-http-client",
+    "scrapy",
+    "headless",
+];
+
+/// Endpoint that 404s matching a junk pattern are counted under.
+pub const JUNK_ENDPOINT: &str = "[junk]";
+
+/// Paths probed by vulnerability scanners, counted in aggregate when they 404.
+pub const DEFAULT_JUNK_PATTERNS: &[&str] = &[
+    r"\.(php|asp|aspx|jsp|cgi|env|ini|bak|sql|zip|tar|gz)$",
+    // Dotfiles, but not `/.well-known`.
+    r"^/\.(git|env|svn|hg|ht|aws|ssh|DS_Store|vscode|idea)",
+    r"^/(wp-|wordpress|cgi-bin|phpmyadmin|pma|vendor|admin\.|boaform|actuator)",
+];
+
+/// Known-junk path matcher.
+pub struct JunkFilter {
+    patterns: regex::RegexSet,
+}
+
+impl JunkFilter {
+    /// Compile junk path patterns.
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        match regex::RegexSet::new(patterns) {
+            Ok(patterns) => Ok(Self { patterns }),
+            Err(e) => bail!("Invalid junk path pattern: {e}"),
+        }
+    }
+
+    /// Whether a path is known junk.
+    pub fn is_junk(&self, path: &str) -> bool {
+        self.patterns.is_match(path)
+    }
+}
+
+/// Broad class of a user agent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentClass {
+    Browser,
+    FeedReader,
+    Bot,
+    Other,
+}
+
+impl AgentClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AgentClass::Browser => "browser",
+            AgentClass::FeedReader => "feed_reader",
+            AgentClass::Bot => "bot",
+            AgentClass::Other => "other",
+        }
+    }
+}
+
+/// Anonymized source of a request.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RequestSource {
+    /// Host of the `Referer` header, empty if missing or invalid.
+    pub referrer_host: String,
+    /// Broad class of the user agent.
+    pub agent_class: AgentClass,
+    /// User agent family from a fixed list (e.g., `Firefox`, `Feedly`).
+    pub agent_family: String,
+}
+
+/// Subscriber count reported by a feed aggregator.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FeedSubscribers {
+    /// Aggregator name (e.g., `Feedly`).
+    pub aggregator: String,
+    /// Reported number of subscribers.
+    pub subscribers: u64,
+}
+
+/// User agent classifier.
+pub struct AgentClassifier {
+    /// Known bots, taken from robots.txt.
+    bots: Vec<String>,
+}
+
+impl AgentClassifier {
+    /// Create a classifier, reading known bots from robots.txt.
+    pub fn new(packed_data: &PackedData) -> Self {
+        let bots = match packed_data.read_text("resources/robots.txt") {
+            Ok(robots) => Self::parse_robots(&robots),
+            Err(e) => {
+                tracing::warn!("Unable to read known bots from robots.txt: {e}");
+                Vec::new()
+            }
+        };
+        Self { bots }
+    }
+
+    /// Collect named user agents from robots.txt.
+    fn parse_robots(robots: &str) -> Vec<String> {
+        robots
+            .lines()
+            .filter_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                if !key.trim().eq_ignore_ascii_case("user-agent") {
+                    return None;
+                }
+                let value = value.split('#').next().unwrap_or_default().trim();
+                match value {
+                    "" | "*" => None,
+                    value => Some(value.to_string()),
+                }
+            })
+            .collect()
+    }
+
+    /// Classify a request from its headers.
+    pub fn classify(&self, headers: &HeaderMap) -> RequestSource {
+        let referrer_host = headers
+            .get(axum::http::header::REFERER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(referrer_host)
+            .unwrap_or_default();
+        let user_agent = headers
+            .get(axum::http::header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        let (agent_class, agent_family) = self.classify_agent(user_agent);
+
+        RequestSource {
+            referrer_host,
+            agent_class,
+            agent_family,
+        }
+    }
+
+    /// Parse the subscriber count a feed aggregator reports in its user agent
+    /// (e.g., `Feedly/1.0 (+http://www.feedly.com/fetcher.html; 42 subscribers)`).
+    pub fn feed_subscribers(&self, headers: &HeaderMap) -> Option<FeedSubscribers> {
+        let user_agent = headers.get(axum::http::header::USER_AGENT)?.to_str().ok()?;
+        let subscribers = parse_subscribers(user_agent)?;
+        let aggregator = match self.classify_agent(user_agent) {
+            (_, family) if family != "other" => family,
+            // Fall back to the product name of unknown aggregators.
+            _ => user_agent
+                .split(['/', ' ', ';', '('])
+                .find(|token| !token.is_empty())?
+                .chars()
+                .take(64)
+                .collect(),
+        };
+
+        Some(FeedSubscribers {
+            aggregator,
+            subscribers,
+        })
+    }
+
+    /// Classify a user agent string.
+    pub fn classify_agent(&self, user_agent: &str) -> (AgentClass, String) {
+        let lower = user_agent.to_lowercase();
+        if lower.trim().is_empty() {
+            return (AgentClass::Other, "unknown".into());
+        }
+
+        if let Some(reader) = find_marker(&lower, FEED_READERS.iter().copied()) {
+            return (AgentClass::FeedReader, reader.into());
+        }
+        if let Some(bot) = find_marker(&lower, self.bots.iter().map(String::as_str)) {
+            return (AgentClass::Bot, bot.into());
+        }
+        if FEED_READER_MARKERS
+            .iter()
+            .any(|marker| lower.contains(marker))
+        {
+            return (AgentClass::FeedReader, "other".into());
+        }
+        if BOT_MARKERS.iter().any(|marker| lower.contains(marker)) {
+            return (AgentClass::Bot, "other".into());
+        }
+
+        let browser = if lower.contains("edg/") {
+            Some("Edge")
+        } else if lower.contains("opr/") {
+            Some("Opera")
+        } else if lower.contains("firefox/") {
+            Some("Firefox")
+        } else if lower.contains("chrome/") || lower.contains("chromium/") {
+            Some("Chrome")
+        } else if lower.contains("safari/") {
+            Some("Safari")
+        } else if lower.starts_with("mozilla/") {
+            Some("other")
+        } else {
+            None
+        };
+        match browser {
+            Some(browser) => (AgentClass::Browser, browser.into()),
+            None => (AgentClass::Other, "other".into()),
+        }
+    }
+}
+
+/// Find the first name contained in a lowercase user agent.
+fn find_marker<'a>(lower: &str, names: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    names
+        .filter(|name| !name.is_empty())
+        .find(|name| lower.contains(&name.to_lowercase()))
+}
+
+/// Number preceding `subscribers` in a user agent.
+fn parse_subscribers(user_agent: &str) -> Option<u64> {
+    let lower = user_agent.to_lowercase();
+    let end = lower.find("subscriber")?;
+    let before = lower[..end].trim_end();
+    let start = before
+        .rfind(|c: char| !c.is_ascii_digit())
+        .map_or(0, |i| i + 1);
+    before[start..].parse().ok()
+}
+
+/// Where a request was linked from: the referring path for pages on this
+/// site, or the referring host for other sites.
+pub fn link_referrer(headers: &HeaderMap) -> Option<String> {
+    let uri: Uri = headers
+        .get(axum::http::header::REFERER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    let host = uri.host()?.trim_start_matches("www.").to_lowercase();
+    let request_host = headers
+        .get(axum::http::header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.split(':').next().unwrap_or_default().to_lowercase());
+    match host == SITE_HOST || Some(&host) == request_host.as_ref() {
+        true => Some(uri.path().to_string()),
+        false => Some(host),
+    }
+}
+
+/// Host of a `Referer` header value.
+pub fn referrer_host(referer: &str) -> Option<String> {
+    let uri: Uri = referer.trim().parse().ok()?;
+    uri.host()
+        .map(|host| host.trim_start_matches("www.").to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analytics::*;
+
+    fn classifier() -> AgentClassifier {
+        AgentClassifier {
+            bots: AgentClassifier::parse_robots(
+                "# comment\nUser-agent: GPTBot\nUser-agent: CCBot\nUser-agent: *\nDisallow: /\n",
+            ),
+        }
+    }
+
+    #[test]
+    fn classify_agents() {
+        let classifier = classifier();
+        let cases = [
+            (
+                "Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Gecko/20100101 Firefox/128.0",
+                AgentClass::Browser,
+                "Firefox",
+            ),
+            (
+                "Mozilla/5.0 (Macintosh) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.0 Safari/605.1.15",
+                AgentClass::Browser,
+                "Safari",
+            ),
+            (
+                "Feedly/1.0 (+http://www.feedly.com/fetcher.html; 42 subscribers; like FeedFetcher-Google)",
+                AgentClass::FeedReader,
+                "Feedly",
+            ),
+            (
+                "Mozilla/5.0 AppleWebKit/537.36 (KHTML, like Gecko; compatible; GPTBot/1.2)",
+                AgentClass::Bot,
+                "GPTBot",
+            ),
+            ("curl/8.5.0", AgentClass::Bot, "other"),
+            ("", AgentClass::Other, "unknown"),
+        ];
+        for (agent, class, family) in cases {
+            assert_eq!(
+                classifier.classify_agent(agent),
+                (class, family.to_string()),
+                "{agent}"
+            );
+        }
+    }
+
+    #[test]
+    fn subscriber_counts() {
+        let classifier = classifier();
+        let subscribers = |agent: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(axum::http::header::USER_AGENT, agent.parse().unwrap());
+            classifier
+                .feed_subscribers(&headers)
+                .map(|found| (found.aggregator, found.subscribers))
+        };
+        assert_eq!(
+            subscribers("Feedly/1.0 (+http://www.feedly.com/fetcher.html; 42 subscribers; like FeedFetcher-Google)"),
+            Some(("Feedly".into(), 42))
+        );
+        assert_eq!(
+            subscribers("NewsBlur Feed Fetcher - 1 subscriber - https://www.newsblur.com/site/123"),
+            Some(("NewsBlur".into(), 1))
+        );
+        assert_eq!(
+            subscribers("CoolReader/2.0 (7 subscribers)"),
+            Some(("CoolReader".into(), 7))
+        );
+        assert_eq!(subscribers("Feedbin feed-id:1 - many subscribers"), None);
+        assert_eq!(subscribers("Mozilla/5.0 Firefox/128.0"), None);
+    }
+
+    #[test]
+    fn junk_paths() {
+        let patterns: Vec<String> = DEFAULT_JUNK_PATTERNS
+            .iter()
+            .map(|pattern| pattern.to_string())
+            .collect();
+        let filter = JunkFilter::new(&patterns).unwrap();
+        for path in [
+            "/wp-login.php",
+            "/.env",
+            "/.git/config",
+            "/.htaccess",
+            "/.aws/credentials",
+            "/cgi-bin/luci",
+            "/backup.sql",
+            "/wp-content/plugins/x",
+        ] {
+            assert!(filter.is_junk(path), "{path}");
+        }
+        for path in [
+            "/blog/scrapers",
+            "/links",
+            "/blog/tag/dev",
+            "/media/catman.png",
+            "/.well-known/security.txt",
+            "/.well-known/acme-challenge/token",
+        ] {
+            assert!(!filter.is_junk(path), "{path}");
+        }
+        assert!(JunkFilter::new(&["(".to_string()]).is_err());
+    }
+
+    #[test]
+    fn link_referrers() {
+        let referrer = |referer: &str, host: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(axum::http::header::REFERER, referer.parse().unwrap());
+            headers.insert(axum::http::header::HOST, host.parse().unwrap());
+            link_referrer(&headers)
+        };
+        assert_eq!(
+            referrer("https://hachha.dev/blog/scrapers?x=1", "hachha.dev"),
+            Some("/blog/scrapers".into())
+        );
+        assert_eq!(
+            referrer("http://127.0.0.1:8180/links", "127.0.0.1:8180"),
+            Some("/links".into())
+        );
+        assert_eq!(
+            referrer("https://www.example.com/post", "hachha.dev"),
+            Some("example.com".into())
+        );
+        assert_eq!(link_referrer(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn referrer_hosts() {
+        assert_eq!(
+            referrer_host("https://www.Example.com/some/post?q=1"),
+            Some("example.com".into())
+        );
+        assert_eq!(
+            referrer_host("https://hachha.dev/blog"),
+            Some("hachha.dev".into())
+        );
+        assert_eq!(referrer_host("not a url"), None);
+    }
+}
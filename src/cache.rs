@@ -3,7 +3,8 @@
 use super::*;
 
 /// State for cached item.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum CachedItemState {
     /// Item is not in cache
     Missing,
@@ -13,6 +14,25 @@ pub enum CachedItemState {
     Expired,
 }
 
+/// Approximate in-memory size of a cached item.
+pub trait CacheWeight {
+    /// Size of the item in bytes.
+    fn weight(&self) -> usize;
+}
+
+/// Summary of a single cache entry.
+#[derive(Debug, Serialize)]
+pub struct CacheEntryInfo {
+    /// Cache key.
+    pub key: String,
+    /// Whether the entry is active or expired.
+    pub state: CachedItemState,
+    /// Seconds since the entry was last updated.
+    pub age_secs: f32,
+    /// Approximate size of the entry in bytes.
+    pub size_bytes: usize,
+}
+
 /// Entry inside of cache.
 struct CacheEntry<T> {
     entry: T,
@@ -32,10 +52,6 @@ impl<T> CacheEntry<T> {
 
     /// Check if entry is expired.
     fn is_expired(&self, timeout: f32) -> bool {
-        // Always reload in debug
-        if cfg!(debug_assertions) {
-            return true;
-        }
         // Check timeout
         let timeout = self.timeout_override.unwrap_or(timeout);
         let time_since_update: f32 = self.update_time.elapsed().as_secs_f32();
@@ -136,6 +152,56 @@ impl<T: Clone> Cache<T> {
     }
 }
 
+impl<T: Clone + CacheWeight> Cache<T> {
+    /// List all entries in the cache, sorted by key.
+    pub async fn entries(&self) -> Vec<CacheEntryInfo> {
+        let entries = self.entries.read().await;
+        let mut infos: Vec<CacheEntryInfo> = entries
+            .iter()
+            .map(|(key, entry)| CacheEntryInfo {
+                key: key.clone(),
+                state: match entry.is_expired(self.timeout) {
+                    true => CachedItemState::Expired,
+                    false => CachedItemState::Active,
+                },
+                age_secs: entry.update_time.elapsed().as_secs_f32(),
+                size_bytes: entry.entry.weight(),
+            })
+            .collect();
+        infos.sort_by(|a, b| a.key.cmp(&b.key));
+        infos
+    }
+}
+
+#[allow(unused)]
+impl<T> Cache<T> {
+    /// Remove a single item from the cache. Returns the number of removed items.
+    pub async fn purge(&self, name: &str) -> usize {
+        let mut entries = self.entries.write().await;
+        match entries.remove(name) {
+            Some(_) => 1,
+            None => 0,
+        }
+    }
+
+    /// Remove all items whose key starts with `prefix`. Returns the number of
+    /// removed items.
+    pub async fn purge_prefix(&self, prefix: &str) -> usize {
+        let mut entries = self.entries.write().await;
+        let before = entries.len();
+        entries.retain(|key, _| !key.starts_with(prefix));
+        before - entries.len()
+    }
+
+    /// Remove every item from the cache. Returns the number of removed items.
+    pub async fn purge_all(&self) -> usize {
+        let mut entries = self.entries.write().await;
+        let count = entries.len();
+        entries.clear();
+        count
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::cache::*;
@@ -157,4 +223,20 @@ mod tests {
         assert_eq!(baz_result, Err(CachedItemState::Missing));
         assert_eq!(cache.retrieve_force("baz").await, None);
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn purging() {
+        let cache: Cache<i8> = Cache::new(f32::INFINITY);
+        cache.update("blog", 1).await;
+        cache.update("blog/foo", 2).await;
+        cache.update("blog/bar", 3).await;
+        cache.update("links", 4).await;
+
+        assert_eq!(cache.purge("missing").await, 0);
+        assert_eq!(cache.purge("blog").await, 1);
+        assert_eq!(cache.purge_prefix("blog/").await, 2);
+        assert!(cache.in_cache("links").await);
+        assert_eq!(cache.purge_all().await, 1);
+        assert_eq!(cache.get_state("links").await, CachedItemState::Missing);
+    }
 }
@@ -0,0 +1,222 @@
+//! Command-line subcommands.
+
+use super::*;
+
+use clap::Subcommand;
+use db::export::{ExportTable, TimeRange};
+use site::SiteConfig;
+
+/// Site subcommands.
+#[derive(Subcommand)]
+pub enum Command {
+    /// Serve the site (default).
+    Serve,
+    /// Manage the site database.
+    #[command(subcommand)]
+    Db(DbCommand),
+    /// Print analytics reports.
+    #[command(subcommand)]
+    Report(ReportCommand),
+}
+
+/// Database subcommands.
+#[derive(Subcommand)]
+pub enum DbCommand {
+    /// Apply pending schema migrations.
+    Migrate,
+    /// Show applied and pending schema migrations.
+    Status,
+    /// Run rollup and retention jobs once.
+    Maintain,
+    /// Snapshot the database, prune old snapshots and checkpoint the
+    /// write-ahead log. The server locks the database, so use
+    /// `POST /admin/backup` or `--backup-interval-hours` while it's running.
+    Backup {
+        /// Also vacuum the live database after the snapshot, reclaiming space
+        /// left by pruned rows.
+        #[arg(long)]
+        vacuum: bool,
+    },
+    /// Export analytics history.
+    Export {
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = ExportFormat::Jsonl)]
+        format: ExportFormat,
+        /// Table to export as CSV. JSON lines always include every table.
+        #[arg(long, value_enum, default_value_t = ExportTable::EndpointHistory)]
+        table: ExportTable,
+        /// First day to include (e.g., 2026-01-31).
+        #[arg(long)]
+        since: Option<chrono::NaiveDate>,
+        /// Last day to include.
+        #[arg(long)]
+        until: Option<chrono::NaiveDate>,
+        /// Output file, stdout by default.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Merge a JSON lines export into the database.
+    Import {
+        /// Export file, or `-` for stdin.
+        input: PathBuf,
+    },
+}
+
+/// Export output format.
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// JSON lines, which can be imported again.
+    Jsonl,
+    /// CSV of a single table.
+    Csv,
+}
+
+/// Report subcommands.
+#[derive(Subcommand)]
+pub enum ReportCommand {
+    /// Top referrers and feed reader share per week.
+    Sources {
+        /// Number of weeks to include.
+        #[arg(long, default_value_t = 8)]
+        weeks: u32,
+        /// Number of referrers to list per week.
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+    /// Links to missing pages, with suggested redirects.
+    BrokenLinks {
+        /// Number of days to include.
+        #[arg(long, default_value_t = 30)]
+        days: u32,
+    },
+}
+
+/// Run a non-serving subcommand.
+pub async fn run(config: SiteConfig) -> Result<()> {
+    match &config.command {
+        None | Some(Command::Serve) => bail!("Serving is not a CLI subcommand."),
+        Some(Command::Db(command)) => run_db(&config, command).await,
+        Some(Command::Report(command)) => run_report(&config, command).await,
+    }
+}
+
+/// Run a report subcommand.
+async fn run_report(config: &SiteConfig, command: &ReportCommand) -> Result<()> {
+    let db = Database::open(config.database()).await?;
+    match command {
+        ReportCommand::Sources { weeks, top } => {
+            for week in db.weekly_sources(*weeks, *top).await? {
+                println!(
+                    "{}: {} requests, {:.1}% from feed readers",
+                    week.week,
+                    week.total(),
+                    week.feed_reader_share() * 100.0
+                );
+                for (class, count) in week.by_class.iter() {
+                    println!("    {class:<12} {count}");
+                }
+                if !week.top_referrers.is_empty() {
+                    println!("  Top referrers:");
+                }
+                for (host, count) in week.top_referrers.iter() {
+                    println!("    {host:<32} {count}");
+                }
+            }
+        }
+        ReportCommand::BrokenLinks { days } => {
+            let pages = Pages::new(Arc::new(config.load_packed_data()?))?;
+            let links = db.broken_links(*days).await?;
+            for report in pages::admin::broken_link_report(&pages, links) {
+                let suggestion = match &report.suggestion {
+                    Some(target) => format!(" -> {target}"),
+                    None => String::new(),
+                };
+                println!("{} ({} visits){suggestion}", report.endpoint, report.count);
+                for (page, count) in report.internal.iter() {
+                    println!("    linked from page {page:<32} {count}");
+                }
+                for (host, count) in report.external.iter() {
+                    println!("    linked from site {host:<32} {count}");
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Run a database subcommand.
+async fn run_db(config: &SiteConfig, command: &DbCommand) -> Result<()> {
+    let db = Database::open(config.database()).await?;
+    match command {
+        DbCommand::Migrate => {
+            let applied = db.migrate().await?;
+            println!("Applied {applied} migration(s) to {}.", config.database());
+        }
+        DbCommand::Status => {
+            for status in db.migration_status().await? {
+                let state = match status.applied {
+                    Some(timestamp) => match chrono::DateTime::from_timestamp(timestamp, 0) {
+                        Some(dt) => format!("applied {}", dt.format("%Y-%m-%d %H:%M:%S UTC")),
+                        None => format!("applied @{timestamp}"),
+                    },
+                    None => "pending".to_string(),
+                };
+                let unknown = match status.known {
+                    true => "",
+                    false => " (unknown to this binary)",
+                };
+                println!("{:04}_{:<24} {state}{unknown}", status.id, status.name);
+            }
+        }
+        DbCommand::Maintain => {
+            db.migrate().await?;
+            db.run_maintenance(&config.retention()).await?;
+        }
+        DbCommand::Backup { vacuum } => {
+            let path = db.backup(&config.backup()).await?;
+            println!("Wrote snapshot {}.", path.display());
+            if *vacuum {
+                // Reopen with vacuum enabled, which the server never needs.
+                drop(db);
+                Database::open_for_vacuum(config.database())
+                    .await?
+                    .vacuum()
+                    .await?;
+                println!("Vacuumed {}.", config.database());
+            }
+        }
+        DbCommand::Export {
+            format,
+            table,
+            since,
+            until,
+            output,
+        } => {
+            let records = db.export(TimeRange::from_dates(*since, *until)).await?;
+            let mut out: Box<dyn std::io::Write> = match output {
+                Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path)?)),
+                None => Box::new(std::io::stdout().lock()),
+            };
+            match format {
+                ExportFormat::Jsonl => db::export::write_jsonl(&records, &mut out)?,
+                ExportFormat::Csv => db::export::write_csv(&records, *table, &mut out)?,
+            }
+            out.flush()?;
+            if output.is_some() {
+                println!("Exported {} record(s).", records.len());
+            }
+        }
+        DbCommand::Import { input } => {
+            let records = match input.to_str() {
+                Some("-") => db::export::read_jsonl(std::io::stdin().lock())?,
+                _ => db::export::read_jsonl(std::io::BufReader::new(std::fs::File::open(input)?))?,
+            };
+            db.migrate().await?;
+            let imported = db.import(&records).await?;
+            println!("Imported {imported} record(s) into {}.", config.database());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,243 @@
+//! Online database snapshots.
+//!
+//! Snapshots are written with `VACUUM INTO`, which copies a consistent view of
+//! the database while the server keeps writing, and compacts the copy as it
+//! goes. Each snapshot also truncates the write-ahead log. Vacuuming the live
+//! database blocks writers, so it's only done on request.
+
+use super::*;
+
+/// Timestamp in snapshot file names, which sorts chronologically.
+const SNAPSHOT_TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+/// Where snapshots are written and how many are kept.
+#[derive(Clone, Debug)]
+pub struct BackupPolicy {
+    /// Snapshot directory.
+    pub dir: PathBuf,
+    /// Number of snapshots to keep. Zero keeps them all.
+    pub keep: usize,
+}
+
+impl Database {
+    /// Write a timestamped snapshot, prune old snapshots and checkpoint the
+    /// write-ahead log. Returns the snapshot path.
+    pub async fn backup(&self, policy: &BackupPolicy) -> Result<PathBuf> {
+        std::fs::create_dir_all(&policy.dir)?;
+        let path = policy.dir.join(format!(
+            "{}-{}.db",
+            self.snapshot_prefix(),
+            chrono::Utc::now().format(SNAPSHOT_TIMESTAMP_FORMAT)
+        ));
+        let Some(target) = path.to_str() else {
+            bail!("Snapshot path is not utf-8: {}", path.display());
+        };
+
+        let conn = self.db.connect()?;
+        conn.busy_timeout(HISTORY_BUSY_TIMEOUT).ok();
+        if let Err(e) = conn
+            .execute(&format!("VACUUM INTO '{}'", target.replace('\'', "''")), ())
+            .await
+        {
+            bail!("Failed to write snapshot {target}: {e}");
+        }
+        // The snapshot is complete on its own, but an empty write-ahead log is
+        // left next to it.
+        remove_sidecars(&path, true);
+        self.prune_backups(policy)?;
+        self.checkpoint().await?;
+
+        Ok(path)
+    }
+
+    /// Move the write-ahead log into the database file and truncate it.
+    pub async fn checkpoint(&self) -> Result<()> {
+        let conn = self.db.connect()?;
+        conn.busy_timeout(HISTORY_BUSY_TIMEOUT).ok();
+        let mut rows = conn.query("PRAGMA wal_checkpoint(TRUNCATE)", ()).await?;
+        while rows.next().await?.is_some() {}
+
+        Ok(())
+    }
+
+    /// Rebuild the live database file, reclaiming space left by deleted rows.
+    /// Blocks every other connection until it's done, and needs a handle from
+    /// [`Database::open_for_vacuum`].
+    pub async fn vacuum(&self) -> Result<()> {
+        let conn = self.db.connect()?;
+        conn.busy_timeout(HISTORY_BUSY_TIMEOUT).ok();
+        // Turso can't vacuum a database that was never written to.
+        let mut rows = conn.query("PRAGMA page_count", ()).await?;
+        let pages: i64 = match rows.next().await? {
+            Some(row) => row.get(0)?,
+            None => 0,
+        };
+        drop(rows);
+        if pages == 0 {
+            return Ok(());
+        }
+        if let Err(e) = conn.execute("VACUUM", ()).await {
+            bail!("Failed to vacuum database: {e}");
+        }
+
+        Ok(())
+    }
+
+    /// Spawn a task writing a snapshot every `interval`.
+    pub fn spawn_backups(
+        self: Arc<Self>,
+        policy: BackupPolicy,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // Skip the immediate first tick, restarts shouldn't take snapshots.
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                match self.backup(&policy).await {
+                    Ok(path) => tracing::info!("Wrote database snapshot {}.", path.display()),
+                    Err(e) => tracing::error!("Database snapshot failed: {e}"),
+                }
+            }
+        })
+    }
+
+    /// Delete all but the newest `policy.keep` snapshots of this database.
+    /// Returns the number of removed snapshots.
+    fn prune_backups(&self, policy: &BackupPolicy) -> Result<usize> {
+        if policy.keep == 0 {
+            return Ok(0);
+        }
+        let prefix = format!("{}-", self.snapshot_prefix());
+        // Only match `{prefix}{timestamp}.db`, leaving snapshots of databases
+        // that share the prefix (e.g., `site-old.db`) alone.
+        let mut snapshots: Vec<PathBuf> = std::fs::read_dir(&policy.dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(|name| name.strip_prefix(&prefix)?.strip_suffix(".db"))
+                    .is_some_and(|timestamp| {
+                        chrono::NaiveDateTime::parse_from_str(timestamp, SNAPSHOT_TIMESTAMP_FORMAT)
+                            .is_ok()
+                    })
+            })
+            .collect();
+        snapshots.sort();
+
+        let excess = snapshots.len().saturating_sub(policy.keep);
+        for snapshot in snapshots.iter().take(excess) {
+            tracing::debug!("Removing old database snapshot {}.", snapshot.display());
+            std::fs::remove_file(snapshot)?;
+            remove_sidecars(snapshot, false);
+        }
+
+        Ok(excess)
+    }
+
+    /// Name shared by all snapshots of this database.
+    fn snapshot_prefix(&self) -> String {
+        std::path::Path::new(&self.path)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .filter(|stem| !stem.is_empty() && *stem != ":memory:")
+            .unwrap_or("memory")
+            .to_string()
+    }
+}
+
+/// Remove the write-ahead log files of a snapshot, optionally only if empty.
+fn remove_sidecars(snapshot: &std::path::Path, only_empty: bool) {
+    for suffix in ["-wal", "-shm"] {
+        let mut sidecar = snapshot.as_os_str().to_owned();
+        sidecar.push(suffix);
+        let sidecar = PathBuf::from(sidecar);
+        let remove = match only_empty {
+            true => std::fs::metadata(&sidecar).is_ok_and(|meta| meta.len() == 0),
+            false => sidecar.exists(),
+        };
+        if remove {
+            std::fs::remove_file(&sidecar).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::backup::*;
+    use crate::db::testing::TempDatabase;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn snapshots_are_pruned() {
+        let file = TempDatabase::new("backup");
+        let db = Database::new(file.path()).await.unwrap();
+        db.update_endpoint_history(&[EndpointVisit {
+            endpoint: "/blog".into(),
+            options: EndpointHistoryOptions::default(),
+            count: 3,
+        }])
+        .await
+        .unwrap();
+
+        let dir = std::env::temp_dir().join(format!(
+            "hachha-dev-backups-{}-{}",
+            std::process::id(),
+            rand::random::<u64>()
+        ));
+        let policy = BackupPolicy {
+            dir: dir.clone(),
+            keep: 2,
+        };
+        // Snapshots of other databases sharing the name are kept.
+        std::fs::create_dir_all(&dir).unwrap();
+        let stem = std::path::Path::new(file.path())
+            .file_stem()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+        for other in [format!("{stem}-old.db"), format!("{stem}-2.db")] {
+            std::fs::write(dir.join(other), b"").unwrap();
+        }
+        let mut snapshots = Vec::new();
+        for _ in 0..3 {
+            snapshots.push(db.backup(&policy).await.unwrap());
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let remaining = std::fs::read_dir(&dir).unwrap().count();
+        assert_eq!(remaining, 4);
+        assert!(!snapshots[0].exists());
+
+        // The latest snapshot holds the data.
+        let snapshot = Database::open(snapshots[2].to_str().unwrap())
+            .await
+            .unwrap();
+        let conn = snapshot.db.connect().unwrap();
+        let mut rows = conn
+            .query("SELECT count FROM endpoint_history", ())
+            .await
+            .unwrap();
+        let row = rows.next().await.unwrap().unwrap();
+        assert_eq!(row.get::<i64>(0).unwrap(), 3);
+        drop(rows);
+        drop(conn);
+
+        std::fs::remove_dir_all(&dir).ok();
+
+        // Vacuuming in place keeps the data.
+        assert!(db.vacuum().await.is_err());
+        drop(db);
+        let db = Database::open_for_vacuum(file.path()).await.unwrap();
+        db.vacuum().await.unwrap();
+        let conn = db.db.connect().unwrap();
+        let mut rows = conn
+            .query("SELECT count FROM endpoint_history", ())
+            .await
+            .unwrap();
+        let row = rows.next().await.unwrap().unwrap();
+        assert_eq!(row.get::<i64>(0).unwrap(), 3);
+    }
+}
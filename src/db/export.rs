@@ -0,0 +1,497 @@
+//! Analytics export and import.
+//!
+//! Exports are self-describing JSON lines, one record per row, keyed by
+//! endpoint rather than row id so they can be merged into another database.
+//! CSV exports cover a single table and are meant for spreadsheets only.
+
+use super::*;
+
+use std::io::Write;
+
+/// A single exported row.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "table", rename_all = "snake_case")]
+pub enum ExportRecord {
+    EndpointHistory(EndpointHistoryRecord),
+    EndpointDailyHits(EndpointHitsRecord),
+    EndpointMonthlyHits(EndpointHitsRecord),
+    VersionHistory(DeployRecord),
+}
+
+/// Exported `endpoint_history` row.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EndpointHistoryRecord {
+    pub endpoint: String,
+    pub method: String,
+    pub route: Option<String>,
+    pub status: i64,
+    pub first_timestamp: i64,
+    pub last_timestamp: i64,
+    pub count: i64,
+}
+
+/// Exported daily or monthly hits of an endpoint.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EndpointHitsRecord {
+    pub endpoint: String,
+    pub method: String,
+    pub status: i64,
+    /// Start of the day or month (unix timestamp).
+    pub period: i64,
+    pub count: i64,
+}
+
+/// Table exported as CSV.
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum ExportTable {
+    EndpointHistory,
+    VersionHistory,
+}
+
+/// Time range of an export, as unix timestamps. `until` is exclusive.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TimeRange {
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+}
+
+impl TimeRange {
+    /// Range covering whole days, from the start of `since` to the end of
+    /// `until`.
+    pub fn from_dates(since: Option<chrono::NaiveDate>, until: Option<chrono::NaiveDate>) -> Self {
+        let start_of = |date: chrono::NaiveDate| {
+            date.and_time(chrono::NaiveTime::default())
+                .and_utc()
+                .timestamp()
+        };
+        Self {
+            since: since.map(start_of),
+            until: until.and_then(|date| date.succ_opt()).map(start_of),
+        }
+    }
+
+    /// Whether the range leaves out any time at all.
+    pub fn is_bounded(&self) -> bool {
+        self.since.is_some() || self.until.is_some()
+    }
+}
+
+impl Database {
+    /// Collect every exportable row in `range`. Endpoints are included if they
+    /// were visited or have hits at any point in the range. For bounded ranges,
+    /// endpoint counts only cover the exported daily and monthly hits and
+    /// timestamps are clamped to the range, so exports of separate ranges merge
+    /// correctly.
+    pub async fn export(&self, range: TimeRange) -> Result<Vec<ExportRecord>> {
+        let conn = self.db.connect()?;
+        let mut records = Vec::new();
+
+        let mut rows = conn
+            .query(
+                concat!(
+                    "SELECT endpoint, method, route, status, first_timestamp, last_timestamp, count ",
+                    "FROM endpoint_history ",
+                    "WHERE (last_timestamp >= coalesce(?, last_timestamp) ",
+                    "AND first_timestamp < coalesce(?, first_timestamp + 1)) ",
+                    "OR id IN (SELECT endpoint_id FROM endpoint_daily_hits ",
+                    "WHERE day >= coalesce(?, day) AND day < coalesce(?, day + 1)) ",
+                    "OR id IN (SELECT endpoint_id FROM endpoint_monthly_hits ",
+                    "WHERE month >= coalesce(?, month) AND month < coalesce(?, month + 1)) ",
+                    "ORDER BY id",
+                ),
+                (
+                    range.since,
+                    range.until,
+                    range.since,
+                    range.until,
+                    range.since,
+                    range.until,
+                ),
+            )
+            .await?;
+        while let Some(row) = rows.next().await? {
+            records.push(ExportRecord::EndpointHistory(EndpointHistoryRecord {
+                endpoint: row.get(0)?,
+                method: row.get(1)?,
+                route: row.get(2)?,
+                status: row.get(3)?,
+                first_timestamp: row.get(4)?,
+                last_timestamp: row.get(5)?,
+                count: row.get(6)?,
+            }));
+        }
+        drop(rows);
+
+        // Hits per endpoint within the range.
+        let mut range_counts: BTreeMap<(String, String, i64), i64> = BTreeMap::new();
+        for (table, column) in [
+            ("endpoint_daily_hits", "day"),
+            ("endpoint_monthly_hits", "month"),
+        ] {
+            let mut rows = conn
+                .query(
+                    &format!(
+                        "SELECT h.endpoint, h.method, h.status, t.{column}, t.count \
+                         FROM {table} t JOIN endpoint_history h ON h.id = t.endpoint_id \
+                         WHERE t.{column} >= coalesce(?, t.{column}) \
+                         AND t.{column} < coalesce(?, t.{column} + 1) \
+                         ORDER BY t.{column}, h.id"
+                    ),
+                    (range.since, range.until),
+                )
+                .await?;
+            while let Some(row) = rows.next().await? {
+                let hits = EndpointHitsRecord {
+                    endpoint: row.get(0)?,
+                    method: row.get(1)?,
+                    status: row.get(2)?,
+                    period: row.get(3)?,
+                    count: row.get(4)?,
+                };
+                *range_counts
+                    .entry((hits.endpoint.clone(), hits.method.clone(), hits.status))
+                    .or_default() += hits.count;
+                records.push(match column {
+                    "day" => ExportRecord::EndpointDailyHits(hits),
+                    _ => ExportRecord::EndpointMonthlyHits(hits),
+                });
+            }
+        }
+
+        if range.is_bounded() {
+            for record in &mut records {
+                if let ExportRecord::EndpointHistory(endpoint) = record {
+                    let key = (
+                        endpoint.endpoint.clone(),
+                        endpoint.method.clone(),
+                        endpoint.status,
+                    );
+                    endpoint.count = range_counts.get(&key).copied().unwrap_or_default();
+                    let clamp = |timestamp: i64| {
+                        let timestamp = range.since.map_or(timestamp, |since| timestamp.max(since));
+                        range
+                            .until
+                            .map_or(timestamp, |until| timestamp.min(until - 1))
+                    };
+                    endpoint.first_timestamp = clamp(endpoint.first_timestamp);
+                    endpoint.last_timestamp = clamp(endpoint.last_timestamp);
+                }
+            }
+        }
+
+        let mut rows = conn
+            .query(
+                concat!(
+                    "SELECT version, timestamp, commit_hash, build_time, data_hash FROM version_history ",
+                    "WHERE timestamp >= coalesce(?, timestamp) ",
+                    "AND timestamp < coalesce(?, timestamp + 1) ",
+                    "ORDER BY id",
+                ),
+                (range.since, range.until),
+            )
+            .await?;
+        while let Some(row) = rows.next().await? {
+            records.push(ExportRecord::VersionHistory(DeployRecord {
+                version: row.get(0)?,
+                timestamp: row.get(1)?,
+                commit_hash: row.get(2)?,
+                build_time: row.get(3)?,
+                data_hash: row.get(4)?,
+            }));
+        }
+
+        Ok(records)
+    }
+
+    /// Merge exported records in a single transaction. Hit counts are added to
+    /// existing counts, so importing the same export twice counts it twice.
+    /// Returns the number of imported records.
+    pub async fn import(&self, records: &[ExportRecord]) -> Result<usize> {
+        let conn = self.db.connect()?;
+        conn.busy_timeout(HISTORY_BUSY_TIMEOUT).ok();
+        conn.execute("BEGIN IMMEDIATE", ()).await?;
+        let res = async {
+            for record in records {
+                match record {
+                    ExportRecord::EndpointHistory(endpoint) => {
+                        conn.execute(
+                            concat!(
+                                "INSERT INTO endpoint_history(endpoint, method, route, status, count, first_timestamp, last_timestamp) ",
+                                "VALUES(?, ?, ?, ?, ?, ?, ?) ",
+                                "ON CONFLICT (endpoint, method, status) ",
+                                "DO UPDATE SET count = count + excluded.count, ",
+                                "first_timestamp = min(first_timestamp, excluded.first_timestamp), ",
+                                "last_timestamp = max(last_timestamp, excluded.last_timestamp), ",
+                                "route = coalesce(route, excluded.route)",
+                            ),
+                            (
+                                endpoint.endpoint.as_str(),
+                                endpoint.method.as_str(),
+                                endpoint.route.as_deref(),
+                                endpoint.status,
+                                endpoint.count,
+                                endpoint.first_timestamp,
+                                endpoint.last_timestamp,
+                            ),
+                        )
+                        .await?;
+                    }
+                    ExportRecord::EndpointDailyHits(hits) | ExportRecord::EndpointMonthlyHits(hits) => {
+                        let (table, column) = match record {
+                            ExportRecord::EndpointDailyHits(_) => ("endpoint_daily_hits", "day"),
+                            _ => ("endpoint_monthly_hits", "month"),
+                        };
+                        conn.execute(
+                            &format!(
+                                "INSERT INTO {table}(endpoint_id, {column}, count) \
+                                 SELECT id, ?, ? FROM endpoint_history \
+                                 WHERE endpoint = ? AND method = ? AND status = ? \
+                                 ON CONFLICT (endpoint_id, {column}) \
+                                 DO UPDATE SET count = count + excluded.count"
+                            ),
+                            (
+                                hits.period,
+                                hits.count,
+                                hits.endpoint.as_str(),
+                                hits.method.as_str(),
+                                hits.status,
+                            ),
+                        )
+                        .await?;
+                    }
+                    ExportRecord::VersionHistory(version) => {
+                        conn.execute(
+                            concat!(
+                                "INSERT INTO version_history(version, timestamp, commit_hash, build_time, data_hash) ",
+                                "SELECT ?, ?, ?, ?, ? WHERE NOT EXISTS (",
+                                "SELECT 1 FROM version_history WHERE version = ? AND timestamp = ?)",
+                            ),
+                            (
+                                version.version.as_str(),
+                                version.timestamp,
+                                version.commit_hash.as_deref(),
+                                version.build_time.as_deref(),
+                                version.data_hash.as_deref(),
+                                version.version.as_str(),
+                                version.timestamp,
+                            ),
+                        )
+                        .await?;
+                    }
+                }
+            }
+            conn.execute("COMMIT", ()).await?;
+            Ok::<(), turso::Error>(())
+        }
+        .await;
+
+        if let Err(e) = res {
+            conn.execute("ROLLBACK", ()).await.ok();
+            bail!("Failed to import records: {e}");
+        }
+
+        Ok(records.len())
+    }
+}
+
+/// Write records as JSON lines.
+pub fn write_jsonl(records: &[ExportRecord], out: &mut impl Write) -> Result<()> {
+    for record in records {
+        serde_json::to_writer(&mut *out, record)?;
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+/// Read records from JSON lines, skipping blank lines.
+pub fn read_jsonl(input: impl std::io::BufRead) -> Result<Vec<ExportRecord>> {
+    let mut records = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(record) => records.push(record),
+            Err(e) => bail!("Invalid record on line {}: {e}", i + 1),
+        }
+    }
+
+    Ok(records)
+}
+
+/// Write the records of a single table as CSV.
+pub fn write_csv(records: &[ExportRecord], table: ExportTable, out: &mut impl Write) -> Result<()> {
+    match table {
+        ExportTable::EndpointHistory => {
+            writeln!(
+                out,
+                "endpoint,method,route,status,first_timestamp,last_timestamp,count"
+            )?;
+            for record in records {
+                if let ExportRecord::EndpointHistory(row) = record {
+                    writeln!(
+                        out,
+                        "{},{},{},{},{},{},{}",
+                        csv_field(&row.endpoint),
+                        csv_field(&row.method),
+                        csv_field(row.route.as_deref().unwrap_or_default()),
+                        row.status,
+                        row.first_timestamp,
+                        row.last_timestamp,
+                        row.count
+                    )?;
+                }
+            }
+        }
+        ExportTable::VersionHistory => {
+            writeln!(out, "version,timestamp,commit_hash,build_time,data_hash")?;
+            for record in records {
+                if let ExportRecord::VersionHistory(row) = record {
+                    writeln!(
+                        out,
+                        "{},{},{},{},{}",
+                        csv_field(&row.version),
+                        row.timestamp,
+                        csv_field(row.commit_hash.as_deref().unwrap_or_default()),
+                        csv_field(row.build_time.as_deref().unwrap_or_default()),
+                        csv_field(row.data_hash.as_deref().unwrap_or_default())
+                    )?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Quote a CSV field if needed.
+fn csv_field(value: &str) -> Cow<'_, str> {
+    match value.contains([',', '"', '\n', '\r']) {
+        true => Cow::Owned(format!("\"{}\"", value.replace('"', "\"\""))),
+        false => Cow::Borrowed(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::export::*;
+    use crate::db::testing::TempDatabase;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn export_and_merge() {
+        let source_file = TempDatabase::new("export");
+        let source = Database::new(source_file.path()).await.unwrap();
+        let visit = |endpoint: &str, status: u16, count: u64| EndpointVisit {
+            endpoint: endpoint.into(),
+            options: EndpointHistoryOptions::builder()
+                .status(status)
+                .route("/blog/{path}")
+                .build(),
+            count,
+        };
+        source
+            .update_endpoint_history(&[visit("/blog/a", 200, 3), visit("/.env", 404, 1)])
+            .await
+            .unwrap();
+
+        let records = source.export(TimeRange::default()).await.unwrap();
+        let mut jsonl = Vec::new();
+        write_jsonl(&records, &mut jsonl).unwrap();
+        let parsed = read_jsonl(jsonl.as_slice()).unwrap();
+        assert_eq!(parsed, records);
+
+        // Merge into a database that already saw some of the same traffic.
+        let target_file = TempDatabase::new("import");
+        let target = Database::new(target_file.path()).await.unwrap();
+        target
+            .update_endpoint_history(&[visit("/blog/a", 200, 2)])
+            .await
+            .unwrap();
+        target.import(&parsed).await.unwrap();
+
+        let merged = target.export(TimeRange::default()).await.unwrap();
+        let counts: Vec<(String, i64)> = merged
+            .iter()
+            .filter_map(|record| match record {
+                ExportRecord::EndpointHistory(row) => Some((row.endpoint.clone(), row.count)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(counts, vec![("/blog/a".into(), 5), ("/.env".into(), 1)]);
+        let daily: i64 = merged
+            .iter()
+            .filter_map(|record| match record {
+                ExportRecord::EndpointDailyHits(hits) => Some(hits.count),
+                _ => None,
+            })
+            .sum();
+        assert_eq!(daily, 6);
+
+        // Exports of separate days add up to the whole history.
+        let today = chrono::Utc::now().date_naive();
+        let yesterday = today.pred_opt().unwrap();
+        let start = TimeRange::from_dates(Some(yesterday), None).since.unwrap();
+        source
+            .import(&[
+                ExportRecord::EndpointHistory(EndpointHistoryRecord {
+                    endpoint: "/blog/a".into(),
+                    method: "GET".into(),
+                    route: None,
+                    status: 200,
+                    first_timestamp: start,
+                    last_timestamp: start,
+                    count: 4,
+                }),
+                ExportRecord::EndpointDailyHits(EndpointHitsRecord {
+                    endpoint: "/blog/a".into(),
+                    method: "GET".into(),
+                    status: 200,
+                    period: start,
+                    count: 4,
+                }),
+            ])
+            .await
+            .unwrap();
+        let target_file = TempDatabase::new("import-ranged");
+        let target = Database::new(target_file.path()).await.unwrap();
+        for range in [
+            TimeRange::from_dates(None, Some(yesterday)),
+            TimeRange::from_dates(Some(today), None),
+        ] {
+            let records = source.export(range).await.unwrap();
+            target.import(&records).await.unwrap();
+        }
+        let counts = |records: Vec<ExportRecord>| -> Vec<(String, i64, i64)> {
+            records
+                .into_iter()
+                .filter_map(|record| match record {
+                    ExportRecord::EndpointHistory(row) => {
+                        Some((row.endpoint, row.count, row.first_timestamp))
+                    }
+                    _ => None,
+                })
+                .collect()
+        };
+        let whole = counts(source.export(TimeRange::default()).await.unwrap());
+        assert_eq!(whole[0], ("/blog/a".into(), 7, start));
+        assert_eq!(
+            counts(target.export(TimeRange::default()).await.unwrap()),
+            whole
+        );
+
+        // Nothing was visited before the range.
+        let range = TimeRange {
+            since: None,
+            until: Some(0),
+        };
+        assert!(source.export(range).await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn csv_quoting() {
+        assert_eq!(csv_field("/blog"), "/blog");
+        assert_eq!(csv_field("/a,b\"c"), "\"/a,b\"\"c\"");
+    }
+}
@@ -0,0 +1,216 @@
+//! Background endpoint history writer.
+//!
+//! Handlers queue visits on a bounded channel instead of writing inline. A
+//! single task coalesces queued visits and writes them in one transaction per
+//! flush, so request latency never depends on database lock contention.
+
+use super::*;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::{mpsc, Mutex, Notify};
+
+/// Coalesced visits to a single endpoint.
+#[derive(Clone, Debug)]
+pub struct EndpointVisit {
+    /// Visited path.
+    pub endpoint: String,
+    /// History options for the visit.
+    pub options: EndpointHistoryOptions,
+    /// Number of visits.
+    pub count: u64,
+}
+
+/// Handle to the background history writer.
+pub struct HistoryWriter {
+    sender: mpsc::Sender<(String, EndpointHistoryOptions)>,
+    /// Events dropped because the queue was full.
+    dropped: Arc<AtomicU64>,
+    shutdown: Arc<Notify>,
+    task: Mutex<Option<tokio::task::JoinHandle<()>>>,
+}
+
+impl HistoryWriter {
+    /// Spawn a writer flushing queued visits to `db` every `flush_interval`.
+    pub fn spawn(db: Arc<Database>, queue_size: usize, flush_interval: Duration) -> Self {
+        let (sender, receiver) = mpsc::channel(queue_size.max(1));
+        let dropped = Arc::new(AtomicU64::new(0));
+        let shutdown = Arc::new(Notify::new());
+        let task = tokio::spawn(Self::run(
+            db,
+            receiver,
+            flush_interval,
+            dropped.clone(),
+            shutdown.clone(),
+        ));
+
+        Self {
+            sender,
+            dropped,
+            shutdown,
+            task: Mutex::new(Some(task)),
+        }
+    }
+
+    /// Queue a visit. Drops the visit if the queue is full.
+    pub fn record(&self, endpoint: &str, options: EndpointHistoryOptions) {
+        if self
+            .sender
+            .try_send((endpoint.to_string(), options))
+            .is_err()
+        {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Total number of visits dropped under backpressure.
+    #[allow(unused)]
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Flush queued visits and stop the writer.
+    pub async fn shutdown(&self) {
+        let task = self.task.lock().await.take();
+        if let Some(task) = task {
+            self.shutdown.notify_one();
+            if let Err(e) = task.await {
+                tracing::error!("Endpoint history writer failed: {e}");
+            }
+        }
+    }
+
+    /// Writer loop.
+    async fn run(
+        db: Arc<Database>,
+        mut receiver: mpsc::Receiver<(String, EndpointHistoryOptions)>,
+        flush_interval: Duration,
+        dropped: Arc<AtomicU64>,
+        shutdown: Arc<Notify>,
+    ) {
+        let mut pending: HashMap<(String, EndpointHistoryOptions), u64> = HashMap::new();
+        let mut reported_dropped = 0;
+        let mut interval = tokio::time::interval(flush_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                visit = receiver.recv() => match visit {
+                    Some(visit) => *pending.entry(visit).or_default() += 1,
+                    None => break,
+                },
+                _ = interval.tick() => {
+                    Self::flush(&db, &mut pending).await;
+                    let total_dropped = dropped.load(Ordering::Relaxed);
+                    if total_dropped > reported_dropped {
+                        tracing::warn!(
+                            "Dropped {} endpoint history events under backpressure ({total_dropped} total).",
+                            total_dropped - reported_dropped
+                        );
+                        reported_dropped = total_dropped;
+                    }
+                }
+                _ = shutdown.notified() => break,
+            }
+        }
+
+        // Drain anything still queued before exiting.
+        receiver.close();
+        while let Some(visit) = receiver.recv().await {
+            *pending.entry(visit).or_default() += 1;
+        }
+        Self::flush(&db, &mut pending).await;
+        tracing::debug!("Endpoint history writer stopped.");
+    }
+
+    /// Write pending visits. Visits are discarded if the write fails.
+    async fn flush(db: &Database, pending: &mut HashMap<(String, EndpointHistoryOptions), u64>) {
+        if pending.is_empty() {
+            return;
+        }
+        let visits: Vec<EndpointVisit> = pending
+            .drain()
+            .map(|((endpoint, options), count)| EndpointVisit {
+                endpoint,
+                options,
+                count,
+            })
+            .collect();
+        if let Err(e) = db.update_endpoint_history(&visits).await {
+            tracing::error!(
+                "Failed to flush {} endpoint history rows: {e}",
+                visits.len()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::history::*;
+    use crate::db::testing::TempDatabase;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn flushes_on_shutdown() {
+        let file = TempDatabase::new("history");
+        let db = Arc::new(Database::new(file.path()).await.unwrap());
+        let writer = HistoryWriter::spawn(db.clone(), 16, Duration::from_secs(3600));
+        for _ in 0..3 {
+            writer.record("/blog", EndpointHistoryOptions::default());
+        }
+        writer.record("/links", EndpointHistoryOptions::default());
+        writer.shutdown().await;
+
+        let conn = db.db.connect().unwrap();
+        let mut rows = conn
+            .query(
+                "SELECT endpoint, count FROM endpoint_history ORDER BY endpoint",
+                (),
+            )
+            .await
+            .unwrap();
+        let mut counts = Vec::new();
+        while let Some(row) = rows.next().await.unwrap() {
+            counts.push((row.get::<String>(0).unwrap(), row.get::<i64>(1).unwrap()));
+        }
+        assert_eq!(counts, vec![("/blog".into(), 3), ("/links".into(), 1)]);
+        drop(rows);
+
+        let mut rows = conn
+            .query("SELECT SUM(count) FROM endpoint_daily_hits", ())
+            .await
+            .unwrap();
+        let row = rows.next().await.unwrap().unwrap();
+        assert_eq!(row.get::<i64>(0).unwrap(), 4);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn in_memory_database() {
+        let db = Arc::new(Database::new(":memory:").await.unwrap());
+        let writer = HistoryWriter::spawn(db.clone(), 16, Duration::from_secs(3600));
+        writer.record("/blog", EndpointHistoryOptions::default());
+        writer.shutdown().await;
+
+        // The writer's connection and this one share the same database.
+        let conn = db.db.connect().unwrap();
+        let mut rows = conn
+            .query("SELECT count FROM endpoint_history", ())
+            .await
+            .unwrap();
+        let row = rows.next().await.unwrap().unwrap();
+        assert_eq!(row.get::<i64>(0).unwrap(), 1);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn drops_under_backpressure() {
+        let file = TempDatabase::new("backpressure");
+        let db = Arc::new(Database::new(file.path()).await.unwrap());
+        let writer = HistoryWriter::spawn(db, 1, Duration::from_secs(3600));
+        // The writer task can't drain the queue until this test yields, so
+        // only one of these fits.
+        for _ in 0..64 {
+            writer.record("/blog", EndpointHistoryOptions::default());
+        }
+        assert!(writer.dropped() > 0);
+        writer.shutdown().await;
+    }
+}
@@ -0,0 +1,285 @@
+//! Periodic database maintenance.
+
+use super::*;
+
+use crate::analytics::JUNK_ENDPOINT;
+
+/// How long analytics data is kept.
+#[derive(Clone, Copy, Debug)]
+pub struct RetentionPolicy {
+    /// Days of daily hits to keep before rolling them up into monthly hits.
+    pub daily_hits_days: u32,
+    /// Months of monthly hits to keep. Zero keeps them forever.
+    pub monthly_hits_months: u32,
+    /// Days of referrer and user agent counts, and of links to missing pages,
+    /// to keep. Zero keeps them forever.
+    pub request_sources_days: u32,
+    /// Days after which rarely hit error endpoints are deleted. Zero keeps them
+    /// forever.
+    pub junk_days: u32,
+    /// Error endpoints with at most this many hits are deleted after
+    /// `junk_days`.
+    pub junk_max_hits: u32,
+}
+
+impl Database {
+    /// Run all maintenance jobs once.
+    pub async fn run_maintenance(&self, policy: &RetentionPolicy) -> Result<()> {
+        let rolled_up = self.rollup_daily_hits(policy.daily_hits_days).await?;
+        let pruned = self.prune_monthly_hits(policy.monthly_hits_months).await?;
+        let pruned_sources = self
+            .prune_request_sources(policy.request_sources_days)
+            .await?;
+        let pruned_junk = self
+            .prune_junk_endpoints(policy.junk_days, policy.junk_max_hits)
+            .await?;
+        tracing::info!(
+            "Database maintenance: rolled up {rolled_up} daily hit rows, pruned {pruned} monthly hit rows, {pruned_sources} request source rows and {pruned_junk} junk endpoints."
+        );
+
+        Ok(())
+    }
+
+    /// Spawn a task running maintenance jobs every `interval`.
+    pub fn spawn_maintenance(
+        self: Arc<Self>,
+        policy: RetentionPolicy,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.run_maintenance(&policy).await {
+                    tracing::error!("Database maintenance failed: {e}");
+                }
+            }
+        })
+    }
+
+    /// Fold daily hits older than `days` into monthly hits. Returns the number
+    /// of removed daily rows.
+    async fn rollup_daily_hits(&self, days: u32) -> Result<u64> {
+        let conn = self.db.connect()?;
+        conn.busy_timeout(HISTORY_BUSY_TIMEOUT).ok();
+        let cutoff = format!("-{days} days");
+        conn.execute("BEGIN IMMEDIATE", ()).await?;
+        let res = async {
+            conn.execute(
+                concat!(
+                    "INSERT INTO endpoint_monthly_hits(endpoint_id, month, count) ",
+                    "SELECT endpoint_id, unixepoch(day, 'unixepoch', 'start of month'), SUM(count) ",
+                    "FROM endpoint_daily_hits ",
+                    "WHERE day < unixepoch(date('now', ?)) ",
+                    "GROUP BY 1, 2 ",
+                    "ON CONFLICT (endpoint_id, month) ",
+                    "DO UPDATE SET count = count + excluded.count",
+                ),
+                [cutoff.as_str()],
+            )
+            .await?;
+            let removed = conn
+                .execute(
+                    "DELETE FROM endpoint_daily_hits WHERE day < unixepoch(date('now', ?))",
+                    [cutoff.as_str()],
+                )
+                .await?;
+            conn.execute("COMMIT", ()).await?;
+            Ok::<u64, turso::Error>(removed)
+        }
+        .await;
+
+        match res {
+            Ok(removed) => Ok(removed),
+            Err(e) => {
+                conn.execute("ROLLBACK", ()).await.ok();
+                bail!("Failed to roll up daily hits: {e}");
+            }
+        }
+    }
+
+    /// Delete monthly hits older than `months`. Returns the number of removed
+    /// rows.
+    async fn prune_monthly_hits(&self, months: u32) -> Result<u64> {
+        if months == 0 {
+            return Ok(0);
+        }
+        let conn = self.db.connect()?;
+        conn.busy_timeout(HISTORY_BUSY_TIMEOUT).ok();
+        let removed = conn
+            .execute(
+                "DELETE FROM endpoint_monthly_hits WHERE month < unixepoch('now', 'start of month', ?)",
+                [format!("-{months} months")],
+            )
+            .await?;
+
+        Ok(removed)
+    }
+
+    /// Delete request sources and broken links older than `days`. Returns the
+    /// number of removed rows.
+    async fn prune_request_sources(&self, days: u32) -> Result<u64> {
+        if days == 0 {
+            return Ok(0);
+        }
+        let conn = self.db.connect()?;
+        conn.busy_timeout(HISTORY_BUSY_TIMEOUT).ok();
+        let cutoff = format!("-{days} days");
+        let mut removed = conn
+            .execute(
+                "DELETE FROM request_sources WHERE day < unixepoch(date('now', ?))",
+                [cutoff.as_str()],
+            )
+            .await?;
+        removed += conn
+            .execute(
+                "DELETE FROM broken_links WHERE last_timestamp < unixepoch('now', ?)",
+                [cutoff.as_str()],
+            )
+            .await?;
+
+        Ok(removed)
+    }
+
+    /// Delete error endpoints with at most `max_hits` hits that haven't been
+    /// visited in `days`, along with their hits. Returns the number of removed
+    /// endpoints.
+    async fn prune_junk_endpoints(&self, days: u32, max_hits: u32) -> Result<u64> {
+        if days == 0 {
+            return Ok(0);
+        }
+        let conn = self.db.connect()?;
+        conn.busy_timeout(HISTORY_BUSY_TIMEOUT).ok();
+        let cutoff = format!("-{days} days");
+        let junk = concat!(
+            "SELECT id FROM endpoint_history ",
+            "WHERE status >= 400 AND count <= ? AND last_timestamp < unixepoch('now', ?) ",
+            "AND endpoint != ?",
+        );
+        conn.execute("BEGIN IMMEDIATE", ()).await?;
+        let res = async {
+            for table in ["endpoint_daily_hits", "endpoint_monthly_hits"] {
+                conn.execute(
+                    &format!("DELETE FROM {table} WHERE endpoint_id IN ({junk})"),
+                    (max_hits as i64, cutoff.as_str(), JUNK_ENDPOINT),
+                )
+                .await?;
+            }
+            let removed = conn
+                .execute(
+                    &format!("DELETE FROM endpoint_history WHERE id IN ({junk})"),
+                    (max_hits as i64, cutoff.as_str(), JUNK_ENDPOINT),
+                )
+                .await?;
+            conn.execute("COMMIT", ()).await?;
+            Ok::<u64, turso::Error>(removed)
+        }
+        .await;
+
+        match res {
+            Ok(removed) => Ok(removed),
+            Err(e) => {
+                conn.execute("ROLLBACK", ()).await.ok();
+                bail!("Failed to prune junk endpoints: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::maintenance::*;
+    use crate::db::testing::TempDatabase;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn rollup_and_retention() {
+        let file = TempDatabase::new("maintenance");
+        let db = Database::new(file.path()).await.unwrap();
+        let conn = db.db.connect().unwrap();
+        conn.execute_batch(
+            "
+            INSERT INTO endpoint_daily_hits(endpoint_id, day, count) VALUES
+                (1, unixepoch('now', 'start of month', '-2 months'), 2),
+                (1, unixepoch('now', 'start of month', '-2 months', '+3 days'), 3),
+                (1, unixepoch('now', 'start of month', '-14 months'), 7),
+                (1, unixepoch(date('now')), 5);
+            ",
+        )
+        .await
+        .unwrap();
+
+        let policy = RetentionPolicy {
+            daily_hits_days: 7,
+            monthly_hits_months: 12,
+            request_sources_days: 0,
+            junk_days: 0,
+            junk_max_hits: 0,
+        };
+        db.run_maintenance(&policy).await.unwrap();
+
+        let mut rows = conn
+            .query("SELECT SUM(count), COUNT(*) FROM endpoint_daily_hits", ())
+            .await
+            .unwrap();
+        let row = rows.next().await.unwrap().unwrap();
+        assert_eq!(
+            (row.get::<i64>(0).unwrap(), row.get::<i64>(1).unwrap()),
+            (5, 1)
+        );
+        drop(rows);
+
+        let mut rows = conn
+            .query("SELECT count FROM endpoint_monthly_hits", ())
+            .await
+            .unwrap();
+        let mut monthly = Vec::new();
+        while let Some(row) = rows.next().await.unwrap() {
+            monthly.push(row.get::<i64>(0).unwrap());
+        }
+        assert_eq!(monthly, vec![5]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn junk_retention() {
+        let file = TempDatabase::new("junk");
+        let db = Database::new(file.path()).await.unwrap();
+        let conn = db.db.connect().unwrap();
+        conn.execute_batch(
+            "
+            INSERT INTO endpoint_history(id, endpoint, method, status, first_timestamp, last_timestamp, count) VALUES
+                (1, '/old-typo', 'GET', 404, 0, unixepoch('now', '-60 days'), 1),
+                (2, '/old-popular', 'GET', 404, 0, unixepoch('now', '-60 days'), 50),
+                (3, '/new-typo', 'GET', 404, 0, unixepoch('now'), 1),
+                (4, '/old-page', 'GET', 200, 0, unixepoch('now', '-60 days'), 1),
+                (5, '[junk]', 'GET', 404, 0, unixepoch('now', '-60 days'), 1);
+            INSERT INTO endpoint_monthly_hits(endpoint_id, month, count) VALUES
+                (1, unixepoch('now', 'start of month', '-2 months'), 1),
+                (2, unixepoch('now', 'start of month', '-2 months'), 50);
+            ",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(db.prune_junk_endpoints(30, 2).await.unwrap(), 1);
+
+        let mut rows = conn
+            .query("SELECT id FROM endpoint_history ORDER BY id", ())
+            .await
+            .unwrap();
+        let mut remaining = Vec::new();
+        while let Some(row) = rows.next().await.unwrap() {
+            remaining.push(row.get::<i64>(0).unwrap());
+        }
+        assert_eq!(remaining, vec![2, 3, 4, 5]);
+        drop(rows);
+
+        let mut rows = conn
+            .query("SELECT endpoint_id FROM endpoint_monthly_hits", ())
+            .await
+            .unwrap();
+        let row = rows.next().await.unwrap().unwrap();
+        assert_eq!(row.get::<i64>(0).unwrap(), 2);
+        assert!(rows.next().await.unwrap().is_none());
+    }
+}
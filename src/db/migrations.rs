@@ -0,0 +1,320 @@
+//! Versioned schema migrations.
+//!
+//! Migrations are embedded in the binary and applied in order, each inside its
+//! own transaction. Applied migrations are recorded in `schema_migrations`.
+//! Never edit or reorder a migration once it has shipped; add a new one.
+
+use super::*;
+
+/// A single named schema migration.
+pub struct Migration {
+    /// Ordered migration id.
+    pub id: i64,
+    /// Short name of the migration.
+    pub name: &'static str,
+    /// SQL applied by the migration.
+    sql: &'static str,
+}
+
+/// All migrations, in the order they are applied.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        id: 1,
+        name: "initial",
+        sql: include_str!("migrations/0001_initial.sql"),
+    },
+    Migration {
+        id: 2,
+        name: "endpoint_status",
+        sql: include_str!("migrations/0002_endpoint_status.sql"),
+    },
+    Migration {
+        id: 3,
+        name: "endpoint_hits",
+        sql: include_str!("migrations/0003_endpoint_hits.sql"),
+    },
+    Migration {
+        id: 4,
+        name: "request_sources",
+        sql: include_str!("migrations/0004_request_sources.sql"),
+    },
+    Migration {
+        id: 5,
+        name: "feed_subscribers",
+        sql: include_str!("migrations/0005_feed_subscribers.sql"),
+    },
+    Migration {
+        id: 6,
+        name: "deploy_history",
+        sql: include_str!("migrations/0006_deploy_history.sql"),
+    },
+    Migration {
+        id: 7,
+        name: "broken_links",
+        sql: include_str!("migrations/0007_broken_links.sql"),
+    },
+];
+
+/// State of a single migration in a database.
+pub struct MigrationStatus {
+    /// Migration id.
+    pub id: i64,
+    /// Migration name.
+    pub name: String,
+    /// When the migration was applied, if it has been.
+    pub applied: Option<i64>,
+    /// Whether this binary knows about the migration.
+    pub known: bool,
+}
+
+impl Database {
+    /// Apply all pending migrations. Returns the number of applied migrations.
+    pub async fn migrate(&self) -> Result<usize> {
+        self.migrate_to(MIGRATIONS.len()).await
+    }
+
+    /// Apply pending migrations up to (and including) the `count`th.
+    async fn migrate_to(&self, count: usize) -> Result<usize> {
+        let conn = self.db.connect()?;
+        Self::ensure_migration_table(&conn).await?;
+        let applied = Self::applied_migrations(&conn).await?;
+
+        let mut newly_applied = 0;
+        for migration in MIGRATIONS.iter().take(count) {
+            if applied.contains_key(&migration.id) {
+                continue;
+            }
+            tracing::info!(
+                "Applying database migration {:04}_{}.",
+                migration.id,
+                migration.name
+            );
+            Self::apply(&conn, migration).await?;
+            newly_applied += 1;
+        }
+
+        if let Some(latest) = applied.keys().max() {
+            if MIGRATIONS.iter().all(|migration| migration.id < *latest) {
+                tracing::warn!("Database has migrations newer than this binary ({latest}).");
+            }
+        }
+
+        Ok(newly_applied)
+    }
+
+    /// Apply a single migration in a transaction.
+    async fn apply(conn: &turso::Connection, migration: &Migration) -> Result<()> {
+        conn.execute("BEGIN IMMEDIATE", ()).await?;
+        let res = async {
+            conn.execute_batch(migration.sql).await?;
+            conn.execute(
+                "INSERT INTO schema_migrations(id, name, timestamp) VALUES(?, ?, unixepoch('now'))",
+                (migration.id, migration.name),
+            )
+            .await?;
+            conn.execute("COMMIT", ()).await?;
+            Ok::<(), turso::Error>(())
+        }
+        .await;
+
+        if let Err(e) = res {
+            conn.execute("ROLLBACK", ()).await.ok();
+            bail!(
+                "Failed to apply migration {:04}_{}: {e}",
+                migration.id,
+                migration.name
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Get the state of every known and applied migration.
+    pub async fn migration_status(&self) -> Result<Vec<MigrationStatus>> {
+        let conn = self.db.connect()?;
+        Self::ensure_migration_table(&conn).await?;
+        let mut applied = Self::applied_migrations(&conn).await?;
+
+        let mut statuses: Vec<MigrationStatus> = MIGRATIONS
+            .iter()
+            .map(|migration| MigrationStatus {
+                id: migration.id,
+                name: migration.name.to_string(),
+                applied: applied
+                    .remove(&migration.id)
+                    .map(|(_, timestamp)| timestamp),
+                known: true,
+            })
+            .collect();
+        statuses.extend(
+            applied
+                .into_iter()
+                .map(|(id, (name, timestamp))| MigrationStatus {
+                    id,
+                    name,
+                    applied: Some(timestamp),
+                    known: false,
+                }),
+        );
+
+        Ok(statuses)
+    }
+
+    /// Create the migration tracking table.
+    async fn ensure_migration_table(conn: &turso::Connection) -> Result<()> {
+        conn.execute(
+            "
+            -- Table for tracking applied schema migrations.
+            CREATE TABLE IF NOT EXISTS schema_migrations(
+                id INTEGER PRIMARY KEY ASC,
+                -- Migration name.
+                name TEXT NOT NULL,
+                -- The timestamp the migration was applied.
+                timestamp INTEGER NOT NULL
+            ) STRICT;
+            ",
+            (),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get applied migrations by id.
+    async fn applied_migrations(conn: &turso::Connection) -> Result<BTreeMap<i64, (String, i64)>> {
+        let mut rows = conn
+            .query("SELECT id, name, timestamp FROM schema_migrations", ())
+            .await?;
+        let mut applied = BTreeMap::new();
+        while let Some(row) = rows.next().await? {
+            applied.insert(
+                row.get::<i64>(0)?,
+                (row.get::<String>(1)?, row.get::<i64>(2)?),
+            );
+        }
+
+        Ok(applied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::migrations::*;
+    use crate::db::testing::TempDatabase;
+
+    /// Schema created by the site before migrations were introduced.
+    const LEGACY_SCHEMA: &str = "
+        CREATE TABLE IF NOT EXISTS version_history(
+            id INTEGER PRIMARY KEY ASC,
+            version TEXT NOT NULL,
+            timestamp INTEGER NOT NULL
+        ) STRICT;
+        CREATE TABLE IF NOT EXISTS endpoint_history(
+            id INTEGER PRIMARY KEY ASC,
+            endpoint TEXT NOT NULL UNIQUE,
+            first_timestamp INTEGER NOT NULL,
+            last_timestamp INTEGER NOT NULL,
+            count INTEGER NOT NULL DEFAULT 0,
+            valid INTEGER NOT NULL DEFAULT 0
+        ) STRICT;
+        INSERT INTO version_history(version, timestamp) VALUES('0.0.1', 1700000000);
+        INSERT INTO endpoint_history(endpoint, first_timestamp, last_timestamp, count, valid)
+            VALUES('/blog', 1700000000, 1700086400, 12, 0);
+        INSERT INTO endpoint_history(endpoint, first_timestamp, last_timestamp, count, valid)
+            VALUES('/wp-login.php', 1700000000, 1700000000, 3, 1);
+    ";
+
+    async fn count(db: &Database, sql: &str) -> i64 {
+        let conn = db.db.connect().unwrap();
+        let mut rows = conn.query(sql, ()).await.unwrap();
+        rows.next().await.unwrap().unwrap().get::<i64>(0).unwrap()
+    }
+
+    async fn assert_fully_migrated(db: &Database) {
+        let statuses = db.migration_status().await.unwrap();
+        assert_eq!(statuses.len(), MIGRATIONS.len());
+        assert!(statuses.iter().all(|s| s.known && s.applied.is_some()));
+        assert_eq!(db.migrate().await.unwrap(), 0);
+    }
+
+    #[test]
+    fn migrations_are_ordered() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.id, i as i64 + 1, "{}", migration.name);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn upgrade_from_legacy_schema() {
+        let file = TempDatabase::new("legacy");
+        let db = Database::open(file.path()).await.unwrap();
+        db.db
+            .connect()
+            .unwrap()
+            .execute_batch(LEGACY_SCHEMA)
+            .await
+            .unwrap();
+
+        assert_eq!(db.migrate().await.unwrap(), MIGRATIONS.len());
+        assert_fully_migrated(&db).await;
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM endpoint_history").await, 2);
+        assert_eq!(
+            count(
+                &db,
+                "SELECT count FROM endpoint_history WHERE endpoint = '/blog' AND status = 200"
+            )
+            .await,
+            12
+        );
+        assert_eq!(
+            count(
+                &db,
+                "SELECT count FROM endpoint_history WHERE endpoint = '/wp-login.php' AND status = 404"
+            )
+            .await,
+            3
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn failed_migration_rolls_back() {
+        let file = TempDatabase::new("rollback");
+        let db = Database::open(file.path()).await.unwrap();
+        let conn = db.db.connect().unwrap();
+        Database::ensure_migration_table(&conn).await.unwrap();
+        let broken = Migration {
+            id: 999,
+            name: "broken",
+            sql: "CREATE TABLE partial(id INTEGER) STRICT; NOT VALID SQL;",
+        };
+
+        assert!(Database::apply(&conn, &broken).await.is_err());
+        assert_eq!(
+            count(&db, "SELECT COUNT(*) FROM schema_migrations").await,
+            0
+        );
+        assert_eq!(
+            count(
+                &db,
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='partial'"
+            )
+            .await,
+            0
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn upgrade_from_every_migration() {
+        for applied in 0..=MIGRATIONS.len() {
+            let file = TempDatabase::new("upgrade");
+            let db = Database::open(file.path()).await.unwrap();
+            assert_eq!(db.migrate_to(applied).await.unwrap(), applied);
+            assert_eq!(
+                db.migrate().await.unwrap(),
+                MIGRATIONS.len() - applied,
+                "upgrading from migration {applied}"
+            );
+            assert_fully_migrated(&db).await;
+        }
+    }
+}
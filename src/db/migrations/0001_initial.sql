@@ -0,0 +1,23 @@
+-- Table for version tracking.
+CREATE TABLE IF NOT EXISTS version_history(
+    id INTEGER PRIMARY KEY ASC,
+    -- Semver version.
+    version TEXT NOT NULL,
+    -- The timestamp of the upgrade.
+    timestamp INTEGER NOT NULL
+) STRICT;
+
+-- Table for tracking endpoint history.
+CREATE TABLE IF NOT EXISTS endpoint_history(
+    id INTEGER PRIMARY KEY ASC,
+    -- Semver version.
+    endpoint TEXT NOT NULL UNIQUE,
+    -- The timestamp of the first attempt.
+    first_timestamp INTEGER NOT NULL,
+    -- The timestamp of the last attempt.
+    last_timestamp INTEGER NOT NULL,
+    -- Number of times the timestamp was hit.
+    count INTEGER NOT NULL DEFAULT 0,
+    -- Whether or not the endpoint is valid.
+    valid INTEGER NOT NULL DEFAULT 0
+) STRICT;
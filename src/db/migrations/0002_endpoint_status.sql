@@ -0,0 +1,38 @@
+-- Replace the endpoint `valid` flag with the response status, request method
+-- and matched route template.
+CREATE TABLE endpoint_history_v2(
+    id INTEGER PRIMARY KEY ASC,
+    -- Requested path.
+    endpoint TEXT NOT NULL,
+    -- Request method.
+    method TEXT NOT NULL DEFAULT 'GET',
+    -- Matched route template (e.g., `/blog/{path}`), NULL when unmatched.
+    route TEXT,
+    -- Response status code.
+    status INTEGER NOT NULL,
+    -- The timestamp of the first attempt.
+    first_timestamp INTEGER NOT NULL,
+    -- The timestamp of the last attempt.
+    last_timestamp INTEGER NOT NULL,
+    -- Number of times the endpoint was hit.
+    count INTEGER NOT NULL DEFAULT 0,
+    UNIQUE(endpoint, method, status)
+) STRICT;
+
+-- `valid` was set for missing pages and unset for served pages.
+INSERT INTO endpoint_history_v2(
+    id, endpoint, method, route, status, first_timestamp, last_timestamp, count
+)
+SELECT
+    id,
+    endpoint,
+    'GET',
+    NULL,
+    CASE valid WHEN 0 THEN 200 ELSE 404 END,
+    first_timestamp,
+    last_timestamp,
+    count
+FROM endpoint_history;
+
+DROP TABLE endpoint_history;
+ALTER TABLE endpoint_history_v2 RENAME TO endpoint_history;
@@ -0,0 +1,23 @@
+-- Daily hit counts per endpoint, for charting traffic over time.
+CREATE TABLE IF NOT EXISTS endpoint_daily_hits(
+    -- Row in endpoint_history.
+    endpoint_id INTEGER NOT NULL,
+    -- Start of the day (unix timestamp, UTC).
+    day INTEGER NOT NULL,
+    -- Number of hits during the day.
+    count INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY(endpoint_id, day)
+) STRICT;
+CREATE INDEX IF NOT EXISTS endpoint_daily_hits_day ON endpoint_daily_hits(day);
+
+-- Monthly hit counts per endpoint, rolled up from expired daily hits.
+CREATE TABLE IF NOT EXISTS endpoint_monthly_hits(
+    -- Row in endpoint_history.
+    endpoint_id INTEGER NOT NULL,
+    -- Start of the month (unix timestamp, UTC).
+    month INTEGER NOT NULL,
+    -- Number of hits during the month.
+    count INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY(endpoint_id, month)
+) STRICT;
+CREATE INDEX IF NOT EXISTS endpoint_monthly_hits_month ON endpoint_monthly_hits(month);
@@ -0,0 +1,15 @@
+-- Daily request counts by referrer host and user agent family. No IP
+-- addresses or full user agents are stored.
+CREATE TABLE IF NOT EXISTS request_sources(
+    -- Start of the day (unix timestamp, UTC).
+    day INTEGER NOT NULL,
+    -- Host of the `Referer` header, empty when missing.
+    referrer_host TEXT NOT NULL DEFAULT '',
+    -- Broad user agent class (browser, feed_reader, bot, other).
+    agent_class TEXT NOT NULL,
+    -- User agent family (e.g., Firefox, Feedly, GPTBot).
+    agent_family TEXT NOT NULL,
+    -- Number of requests.
+    count INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY(day, referrer_host, agent_class, agent_family)
+) STRICT;
@@ -0,0 +1,12 @@
+-- Latest subscriber count reported by each feed aggregator.
+CREATE TABLE IF NOT EXISTS feed_subscribers(
+    -- Feed path (e.g., /blog.feed).
+    feed TEXT NOT NULL,
+    -- Aggregator name (e.g., Feedly).
+    aggregator TEXT NOT NULL,
+    -- Most recently reported number of subscribers.
+    subscribers INTEGER NOT NULL,
+    -- When the count was last reported (unix timestamp).
+    last_timestamp INTEGER NOT NULL,
+    PRIMARY KEY(feed, aggregator)
+) STRICT;
@@ -0,0 +1,6 @@
+-- Build details of each deploy, recorded on startup. Rows from before this
+-- migration only have a version.
+ALTER TABLE version_history ADD COLUMN commit_hash TEXT;
+ALTER TABLE version_history ADD COLUMN build_time TEXT;
+-- Hash of the loaded packed data.
+ALTER TABLE version_history ADD COLUMN data_hash TEXT;
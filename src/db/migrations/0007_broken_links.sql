@@ -0,0 +1,15 @@
+-- Pages and sites linking to missing endpoints.
+CREATE TABLE IF NOT EXISTS broken_links(
+    -- Missing path.
+    endpoint TEXT NOT NULL,
+    -- Path of the linking page on this site (e.g., /blog/scrapers), or the
+    -- host of the linking site (e.g., news.ycombinator.com).
+    referrer TEXT NOT NULL,
+    -- The timestamp of the first broken visit.
+    first_timestamp INTEGER NOT NULL,
+    -- The timestamp of the last broken visit.
+    last_timestamp INTEGER NOT NULL,
+    -- Number of broken visits.
+    count INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY(endpoint, referrer)
+) STRICT;
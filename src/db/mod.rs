@@ -0,0 +1,346 @@
+//! Database wrapper.
+
+use super::*;
+
+use crate::analytics::{FeedSubscribers, RequestSource, JUNK_ENDPOINT};
+
+mod backup;
+pub mod export;
+mod history;
+mod maintenance;
+mod migrations;
+mod reports;
+
+pub use backup::BackupPolicy;
+pub use history::{EndpointVisit, HistoryWriter};
+pub use maintenance::RetentionPolicy;
+pub use reports::BrokenLink;
+
+/// Database helper.
+/// This is utilized to track persistent state and history within the site.
+pub struct Database {
+    db: turso::Database,
+    /// Database path, or `:memory:`.
+    path: String,
+}
+
+impl Database {
+    /// Create a new database handle, applying any pending migrations.
+    pub async fn new(fname: impl AsRef<str>) -> Result<Self> {
+        let db = Self::open(fname).await?;
+        db.migrate().await?;
+
+        Ok(db)
+    }
+
+    /// Open a database handle without touching the schema.
+    pub async fn open(fname: impl AsRef<str>) -> Result<Self> {
+        Self::build(fname, turso::Builder::new_local).await
+    }
+
+    /// Open a database handle that can run a plain `VACUUM`, which is still
+    /// experimental in turso. Only meant for offline maintenance.
+    pub async fn open_for_vacuum(fname: impl AsRef<str>) -> Result<Self> {
+        Self::build(fname, |path| {
+            turso::Builder::new_local(path).experimental_vacuum(true)
+        })
+        .await
+    }
+
+    /// Open a database handle from a configured builder.
+    async fn build(
+        fname: impl AsRef<str>,
+        builder: impl FnOnce(&str) -> turso::Builder,
+    ) -> Result<Self> {
+        let sqlite_db = match builder(fname.as_ref()).build().await {
+            Ok(sqlite_db) => sqlite_db,
+            Err(e) => {
+                bail!("Failed to initialize database: {e}");
+            }
+        };
+
+        Ok(Self {
+            db: sqlite_db,
+            path: fname.as_ref().to_string(),
+        })
+    }
+
+    /// Add a batch of visits to the endpoint history in a single transaction.
+    pub async fn update_endpoint_history(&self, visits: &[EndpointVisit]) -> Result<()> {
+        let conn = self.db.connect()?;
+        conn.busy_timeout(HISTORY_BUSY_TIMEOUT).ok();
+        conn.execute("BEGIN IMMEDIATE", ()).await?;
+        let res = async {
+            for visit in visits {
+                let mut rows = conn
+                    .query(
+                        concat!(
+                            "INSERT INTO endpoint_history(endpoint, method, route, status, count, first_timestamp, last_timestamp) ",
+                            "VALUES(?, ?, ?, ?, ?, unixepoch('now'), unixepoch('now')) ",
+                            "ON CONFLICT (endpoint, method, status) ",
+                            "DO UPDATE SET ",
+                            "last_timestamp = unixepoch('now'), count = count + excluded.count, ",
+                            "route = coalesce(excluded.route, route) ",
+                            "RETURNING id",
+                        ),
+                        (
+                            visit.endpoint.as_str(),
+                            visit.options.method.as_str(),
+                            visit.options.route.as_deref(),
+                            visit.options.status as i64,
+                            visit.count as i64,
+                        ),
+                    )
+                    .await?;
+                let endpoint_id: Option<i64> = match rows.next().await? {
+                    Some(row) => Some(row.get(0)?),
+                    None => None,
+                };
+                // Finish the statement before starting the next one.
+                while rows.next().await?.is_some() {}
+                let Some(endpoint_id) = endpoint_id else {
+                    continue;
+                };
+                conn.execute(
+                    concat!(
+                        "INSERT INTO endpoint_daily_hits(endpoint_id, day, count) ",
+                        "VALUES(?, unixepoch(date('now')), ?) ",
+                        "ON CONFLICT (endpoint_id, day) ",
+                        "DO UPDATE SET count = count + excluded.count",
+                    ),
+                    (endpoint_id, visit.count as i64),
+                )
+                .await?;
+
+                if let Some(source) = &visit.options.source {
+                    conn.execute(
+                        concat!(
+                            "INSERT INTO request_sources(day, referrer_host, agent_class, agent_family, count) ",
+                            "VALUES(unixepoch(date('now')), ?, ?, ?, ?) ",
+                            "ON CONFLICT (day, referrer_host, agent_class, agent_family) ",
+                            "DO UPDATE SET count = count + excluded.count",
+                        ),
+                        (
+                            source.referrer_host.as_str(),
+                            source.agent_class.as_str(),
+                            source.agent_family.as_str(),
+                            visit.count as i64,
+                        ),
+                    )
+                    .await?;
+                }
+
+                if let Some(referrer) = &visit.options.referrer {
+                    conn.execute(
+                        concat!(
+                            "INSERT INTO broken_links(endpoint, referrer, count, first_timestamp, last_timestamp) ",
+                            "VALUES(?, ?, ?, unixepoch('now'), unixepoch('now')) ",
+                            "ON CONFLICT (endpoint, referrer) ",
+                            "DO UPDATE SET last_timestamp = unixepoch('now'), count = count + excluded.count",
+                        ),
+                        (
+                            visit.endpoint.as_str(),
+                            referrer.as_str(),
+                            visit.count as i64,
+                        ),
+                    )
+                    .await?;
+                }
+
+                if let Some(feed) = &visit.options.feed_subscribers {
+                    conn.execute(
+                        concat!(
+                            "INSERT INTO feed_subscribers(feed, aggregator, subscribers, last_timestamp) ",
+                            "VALUES(?, ?, ?, unixepoch('now')) ",
+                            "ON CONFLICT (feed, aggregator) ",
+                            "DO UPDATE SET subscribers = excluded.subscribers, last_timestamp = excluded.last_timestamp",
+                        ),
+                        (
+                            visit.endpoint.as_str(),
+                            feed.aggregator.as_str(),
+                            feed.subscribers as i64,
+                        ),
+                    )
+                    .await?;
+                }
+            }
+            conn.execute("COMMIT", ()).await?;
+            Ok::<(), turso::Error>(())
+        }
+        .await;
+
+        if let Err(e) = res {
+            conn.execute("ROLLBACK", ()).await.ok();
+            tracing::warn!("Failed to write to endpoint history: {e}");
+            bail!(e);
+        }
+
+        Ok(())
+    }
+}
+
+impl Database {
+    /// Record a deploy in the version history.
+    pub async fn record_deploy(&self, deploy: &DeployRecord) -> Result<()> {
+        let conn = self.db.connect()?;
+        conn.busy_timeout(HISTORY_BUSY_TIMEOUT).ok();
+        conn.execute(
+            concat!(
+                "INSERT INTO version_history(version, timestamp, commit_hash, build_time, data_hash) ",
+                "VALUES(?, ?, ?, ?, ?)",
+            ),
+            (
+                deploy.version.as_str(),
+                deploy.timestamp,
+                deploy.commit_hash.as_deref(),
+                deploy.build_time.as_deref(),
+                deploy.data_hash.as_deref(),
+            ),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// A deploy of the site, as recorded in the version history.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeployRecord {
+    /// Semver version.
+    pub version: String,
+    /// When the deploy started (unix timestamp).
+    pub timestamp: i64,
+    /// Commit the binary was built from.
+    #[serde(default)]
+    pub commit_hash: Option<String>,
+    /// When the binary was built.
+    #[serde(default)]
+    pub build_time: Option<String>,
+    /// Hash of the loaded packed data.
+    #[serde(default)]
+    pub data_hash: Option<String>,
+}
+
+/// How long history writes wait on a locked database.
+const HISTORY_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Details of a request recorded in the endpoint history.
+#[derive(Builder, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EndpointHistoryOptions {
+    /// Response status code.
+    #[builder(default = 200)]
+    pub status: u16,
+    /// Request method.
+    #[builder(default = "GET".to_string(), into)]
+    pub method: String,
+    /// Matched route template (e.g., `/blog/{path}`), if any route matched.
+    #[builder(into)]
+    pub route: Option<String>,
+    /// Anonymized request source, only tracked for pages and feeds.
+    pub source: Option<RequestSource>,
+    /// Page or site linking to a missing endpoint, only tracked for 404s.
+    #[builder(into)]
+    pub referrer: Option<String>,
+    /// Subscriber count reported by a feed aggregator, only tracked for feeds.
+    pub feed_subscribers: Option<FeedSubscribers>,
+}
+
+impl Default for EndpointHistoryOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl EndpointHistoryOptions {
+    /// Queue a visit to be written to the endpoint history. Does nothing when
+    /// analytics are disabled.
+    pub fn write(&self, site: &Site, endpoint: impl AsRef<str>) {
+        if let Some(history) = site.history() {
+            history.record(endpoint.as_ref(), self.clone());
+        }
+    }
+}
+
+/// Middleware recording every response in the endpoint history.
+pub async fn record_endpoint_history(
+    State(site): State<Site>,
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    if site.config().no_analytics {
+        return next.run(request).await;
+    }
+
+    let endpoint = request.uri().path().to_string();
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<axum::extract::MatchedPath>()
+        .map(|path| path.as_str().to_string());
+    let source = site.agent_classifier().classify(request.headers());
+    let feed_subscribers = site.agent_classifier().feed_subscribers(request.headers());
+    let referrer = analytics::link_referrer(request.headers());
+
+    let response = next.run(request).await;
+
+    // Skip sources for assets, which would mostly count page loads again.
+    let content_type = response
+        .headers()
+        .get(axum::http::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let is_feed = content_type.contains("xml") || content_type.contains("feed+json");
+    let is_document = is_feed || content_type.starts_with("text/html");
+
+    // Count scanner probes together rather than one endpoint each.
+    let status = response.status();
+    let (endpoint, route, referrer) = match status == axum::http::StatusCode::NOT_FOUND {
+        true if site.junk_filter().is_junk(&endpoint) => (JUNK_ENDPOINT.to_string(), None, None),
+        true => (endpoint, route, referrer),
+        false => (endpoint, route, None),
+    };
+
+    EndpointHistoryOptions::builder()
+        .status(status.as_u16())
+        .method(method)
+        .maybe_route(route)
+        .maybe_referrer(referrer)
+        .maybe_source(is_document.then_some(source))
+        .maybe_feed_subscribers(
+            feed_subscribers.filter(|_| is_feed && response.status().is_success()),
+        )
+        .build()
+        .write(&site, endpoint);
+    response
+}
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+
+    /// Temporary database file, removed on drop.
+    pub struct TempDatabase(PathBuf);
+
+    impl TempDatabase {
+        pub fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "hachha-dev-{name}-{}-{}.db",
+                std::process::id(),
+                rand::random::<u64>()
+            ));
+            Self(path)
+        }
+
+        pub fn path(&self) -> &str {
+            self.0.to_str().expect("temp path is utf-8")
+        }
+    }
+
+    impl Drop for TempDatabase {
+        fn drop(&mut self) {
+            for suffix in ["", "-wal", "-shm"] {
+                std::fs::remove_file(format!("{}{suffix}", self.path())).ok();
+            }
+        }
+    }
+}
@@ -0,0 +1,473 @@
+//! Analytics reports.
+
+use super::*;
+
+use crate::analytics::{AgentClass, SITE_HOST};
+
+/// Request sources during a single week.
+#[derive(Debug, Default, Serialize)]
+pub struct WeeklySources {
+    /// ISO week-numbering year and week (e.g., `2026-W42`).
+    pub week: String,
+    /// Requests by user agent class.
+    pub by_class: BTreeMap<String, i64>,
+    /// Most common external referrer hosts.
+    pub top_referrers: Vec<(String, i64)>,
+}
+
+impl WeeklySources {
+    /// Total number of requests.
+    pub fn total(&self) -> i64 {
+        self.by_class.values().sum()
+    }
+
+    /// Share of requests made by feed readers.
+    pub fn feed_reader_share(&self) -> f64 {
+        let readers = self
+            .by_class
+            .get(AgentClass::FeedReader.as_str())
+            .copied()
+            .unwrap_or_default();
+        match self.total() {
+            0 => 0.0,
+            total => readers as f64 / total as f64,
+        }
+    }
+}
+
+/// Latest subscriber count of a single feed aggregator.
+#[derive(Debug, Serialize)]
+pub struct FeedSubscriberCount {
+    /// Feed path (e.g., `/blog.feed`).
+    pub feed: String,
+    /// Aggregator name (e.g., `Feedly`).
+    pub aggregator: String,
+    /// Reported number of subscribers.
+    pub subscribers: i64,
+    /// When the count was last reported (unix timestamp).
+    pub last_seen: i64,
+}
+
+impl FeedSubscriberCount {
+    /// Whether this counts readers of the main blog feed, in any format.
+    pub fn is_blog_feed(&self) -> bool {
+        BLOG_FEEDS.contains(&self.feed.as_str())
+    }
+}
+
+/// Formats of the main blog feed, which readers subscribe to one of.
+const BLOG_FEEDS: &[&str] = &["/blog.feed", "/blog.rss", "/blog.json"];
+
+/// Paths of static assets, left out of the page rankings.
+const ASSET_PREFIXES: &[&str] = &[
+    "/styles/",
+    "/fonts/",
+    "/media/",
+    "/blog/media/",
+    "/theme.css",
+    "/favicon.ico",
+    "/robots.txt",
+];
+
+/// Hits of a single endpoint.
+#[derive(Debug, Serialize)]
+pub struct EndpointCount {
+    /// Visited path.
+    pub endpoint: String,
+    /// Response status code.
+    pub status: i64,
+    /// Number of hits.
+    pub count: i64,
+}
+
+/// A link to a missing endpoint.
+#[derive(Debug, Serialize)]
+pub struct BrokenLink {
+    /// Missing path.
+    pub endpoint: String,
+    /// Linking path on this site (starting with `/`), or linking host.
+    pub referrer: String,
+    /// Number of broken visits.
+    pub count: i64,
+    /// When the link was last followed (unix timestamp).
+    pub last_seen: i64,
+}
+
+impl BrokenLink {
+    /// Whether the link is on this site.
+    pub fn is_internal(&self) -> bool {
+        self.referrer.starts_with('/')
+    }
+}
+
+impl Database {
+    /// Most visited GET endpoints with a status in `statuses` over the last
+    /// `days`, excluding static assets. Only daily hits are considered, so
+    /// `days` is effectively capped by the daily hits retention.
+    pub async fn top_endpoints(
+        &self,
+        days: u32,
+        statuses: std::ops::RangeInclusive<u16>,
+        limit: usize,
+    ) -> Result<Vec<EndpointCount>> {
+        let conn = self.db.connect()?;
+        let mut rows = conn
+            .query(
+                concat!(
+                    "SELECT h.endpoint, h.status, SUM(d.count) AS hits ",
+                    "FROM endpoint_daily_hits d JOIN endpoint_history h ON h.id = d.endpoint_id ",
+                    "WHERE d.day >= unixepoch(date('now', ?)) AND h.method = 'GET' ",
+                    "AND h.status >= ? AND h.status <= ? ",
+                    "GROUP BY h.endpoint, h.status ",
+                    "ORDER BY hits DESC, h.endpoint",
+                ),
+                (
+                    format!("-{days} days"),
+                    *statuses.start() as i64,
+                    *statuses.end() as i64,
+                ),
+            )
+            .await?;
+        let mut counts = Vec::new();
+        while let Some(row) = rows.next().await? {
+            let endpoint: String = row.get(0)?;
+            if ASSET_PREFIXES
+                .iter()
+                .any(|prefix| endpoint.starts_with(prefix))
+            {
+                continue;
+            }
+            counts.push(EndpointCount {
+                endpoint,
+                status: row.get(1)?,
+                count: row.get(2)?,
+            });
+            if counts.len() == limit {
+                break;
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// Successful daily hits of every endpoint matching `route` over the last
+    /// `days`, keyed by endpoint and then by day (unix timestamp).
+    pub async fn daily_hits_by_route(
+        &self,
+        route: &str,
+        days: u32,
+    ) -> Result<BTreeMap<String, BTreeMap<i64, i64>>> {
+        let conn = self.db.connect()?;
+        let mut rows = conn
+            .query(
+                concat!(
+                    "SELECT h.endpoint, d.day, SUM(d.count) ",
+                    "FROM endpoint_daily_hits d JOIN endpoint_history h ON h.id = d.endpoint_id ",
+                    "WHERE h.route = ? AND h.status >= 200 AND h.status < 300 ",
+                    "AND d.day >= unixepoch(date('now', ?)) ",
+                    "GROUP BY h.endpoint, d.day",
+                ),
+                (route, format!("-{days} days")),
+            )
+            .await?;
+        let mut hits: BTreeMap<String, BTreeMap<i64, i64>> = BTreeMap::new();
+        while let Some(row) = rows.next().await? {
+            hits.entry(row.get(0)?)
+                .or_default()
+                .insert(row.get(1)?, row.get(2)?);
+        }
+
+        Ok(hits)
+    }
+
+    /// Links to endpoints that are still missing, followed within the last
+    /// `days`, most followed first.
+    pub async fn broken_links(&self, days: u32) -> Result<Vec<BrokenLink>> {
+        let conn = self.db.connect()?;
+        let mut rows = conn
+            .query(
+                concat!(
+                    "SELECT b.endpoint, b.referrer, b.count, b.last_timestamp FROM broken_links b ",
+                    "WHERE b.last_timestamp >= unixepoch('now', ?) ",
+                    // Skip links that have since been fixed.
+                    "AND NOT EXISTS (SELECT 1 FROM endpoint_history h WHERE h.endpoint = b.endpoint ",
+                    "AND h.status >= 200 AND h.status < 400 AND h.last_timestamp > b.last_timestamp) ",
+                    "ORDER BY b.count DESC, b.endpoint, b.referrer",
+                ),
+                [format!("-{days} days")],
+            )
+            .await?;
+        let mut links = Vec::new();
+        while let Some(row) = rows.next().await? {
+            links.push(BrokenLink {
+                endpoint: row.get(0)?,
+                referrer: row.get(1)?,
+                count: row.get(2)?,
+                last_seen: row.get(3)?,
+            });
+        }
+
+        Ok(links)
+    }
+
+    /// Most recent deploys, newest first.
+    pub async fn deploys(&self, limit: usize) -> Result<Vec<DeployRecord>> {
+        let conn = self.db.connect()?;
+        let mut rows = conn
+            .query(
+                concat!(
+                    "SELECT version, timestamp, commit_hash, build_time, data_hash ",
+                    "FROM version_history ORDER BY id DESC LIMIT ?",
+                ),
+                [limit as i64],
+            )
+            .await?;
+        let mut deploys = Vec::new();
+        while let Some(row) = rows.next().await? {
+            deploys.push(DeployRecord {
+                version: row.get(0)?,
+                timestamp: row.get(1)?,
+                commit_hash: row.get(2)?,
+                build_time: row.get(3)?,
+                data_hash: row.get(4)?,
+            });
+        }
+
+        Ok(deploys)
+    }
+
+    /// Latest subscriber counts reported within the last `days`, largest first.
+    /// Aggregators that stopped polling drop out of the estimate.
+    pub async fn feed_subscribers(&self, days: u32) -> Result<Vec<FeedSubscriberCount>> {
+        let conn = self.db.connect()?;
+        let mut rows = conn
+            .query(
+                concat!(
+                    "SELECT feed, aggregator, subscribers, last_timestamp FROM feed_subscribers ",
+                    "WHERE last_timestamp >= unixepoch('now', ?) ",
+                    "ORDER BY subscribers DESC, feed, aggregator",
+                ),
+                [format!("-{days} days")],
+            )
+            .await?;
+        let mut counts = Vec::new();
+        while let Some(row) = rows.next().await? {
+            counts.push(FeedSubscriberCount {
+                feed: row.get(0)?,
+                aggregator: row.get(1)?,
+                subscribers: row.get(2)?,
+                last_seen: row.get(3)?,
+            });
+        }
+
+        Ok(counts)
+    }
+
+    /// Estimate the number of blog feed subscribers within the last `days`.
+    /// Tag and links feeds are left out, since their readers mostly follow the
+    /// blog feed as well and would be counted twice.
+    pub async fn blog_feed_subscribers(&self, days: u32) -> Result<i64> {
+        Ok(self
+            .feed_subscribers(days)
+            .await?
+            .iter()
+            .filter(|count| count.is_blog_feed())
+            .map(|count| count.subscribers)
+            .sum())
+    }
+
+    /// Summarize request sources per ISO week, most recent first.
+    pub async fn weekly_sources(&self, weeks: u32, top: usize) -> Result<Vec<WeeklySources>> {
+        let conn = self.db.connect()?;
+        let since = format!("-{} days", weeks.saturating_mul(7));
+        let mut report: BTreeMap<String, WeeklySources> = BTreeMap::new();
+
+        // SQLite has no ISO week format, so days are grouped into weeks here.
+        let mut rows = conn
+            .query(
+                concat!(
+                    "SELECT day, agent_class, SUM(count) ",
+                    "FROM request_sources ",
+                    "WHERE day >= unixepoch(date('now', ?)) ",
+                    "GROUP BY day, agent_class",
+                ),
+                [since.as_str()],
+            )
+            .await?;
+        while let Some(row) = rows.next().await? {
+            let week = iso_week(row.get(0)?);
+            *report
+                .entry(week.clone())
+                .or_insert_with(|| WeeklySources {
+                    week,
+                    ..Default::default()
+                })
+                .by_class
+                .entry(row.get(1)?)
+                .or_default() += row.get::<i64>(2)?;
+        }
+        drop(rows);
+
+        let mut rows = conn
+            .query(
+                concat!(
+                    "SELECT day, referrer_host, SUM(count) ",
+                    "FROM request_sources ",
+                    "WHERE day >= unixepoch(date('now', ?)) AND referrer_host != '' AND referrer_host != ? ",
+                    "GROUP BY day, referrer_host",
+                ),
+                (since.as_str(), SITE_HOST),
+            )
+            .await?;
+        let mut referrers: BTreeMap<String, BTreeMap<String, i64>> = BTreeMap::new();
+        while let Some(row) = rows.next().await? {
+            *referrers
+                .entry(iso_week(row.get(0)?))
+                .or_default()
+                .entry(row.get(1)?)
+                .or_default() += row.get::<i64>(2)?;
+        }
+        for (week, hosts) in referrers {
+            if let Some(sources) = report.get_mut(&week) {
+                let mut hosts: Vec<(String, i64)> = hosts.into_iter().collect();
+                hosts.sort_by_key(|(_, hits)| std::cmp::Reverse(*hits));
+                hosts.truncate(top);
+                sources.top_referrers = hosts;
+            }
+        }
+
+        Ok(report.into_values().rev().collect())
+    }
+}
+
+/// Label a day, given as a unix timestamp, with its ISO week (e.g., `2026-W42`).
+fn iso_week(day: i64) -> String {
+    let week = chrono::DateTime::from_timestamp(day, 0)
+        .unwrap_or_default()
+        .iso_week();
+    format!("{}-W{:02}", week.year(), week.week())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analytics::FeedSubscribers;
+    use crate::db::reports::*;
+    use crate::db::testing::TempDatabase;
+    use crate::db::{DeployRecord, EndpointHistoryOptions, EndpointVisit};
+
+    fn feed_visit(aggregator: &str, subscribers: u64) -> EndpointVisit {
+        EndpointVisit {
+            endpoint: "/blog.feed".into(),
+            options: EndpointHistoryOptions::builder()
+                .feed_subscribers(FeedSubscribers {
+                    aggregator: aggregator.into(),
+                    subscribers,
+                })
+                .build(),
+            count: 1,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn latest_feed_subscribers() {
+        let file = TempDatabase::new("subscribers");
+        let db = Database::new(file.path()).await.unwrap();
+        db.update_endpoint_history(&[feed_visit("Feedly", 40), feed_visit("Inoreader", 3)])
+            .await
+            .unwrap();
+        db.update_endpoint_history(&[feed_visit("Feedly", 42)])
+            .await
+            .unwrap();
+
+        let counts: Vec<(String, i64)> = db
+            .feed_subscribers(14)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|count| (count.aggregator, count.subscribers))
+            .collect();
+        assert_eq!(counts, vec![("Feedly".into(), 42), ("Inoreader".into(), 3)]);
+
+        // Readers of other feeds are left out of the estimate.
+        db.update_endpoint_history(&[EndpointVisit {
+            endpoint: "/blog/tags/rust.feed".into(),
+            ..feed_visit("Feedly", 30)
+        }])
+        .await
+        .unwrap();
+        assert_eq!(db.blog_feed_subscribers(14).await.unwrap(), 45);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn deploy_history() {
+        let file = TempDatabase::new("deploys");
+        let db = Database::new(file.path()).await.unwrap();
+        for (version, timestamp) in [("0.18.0", 100), ("0.19.0", 200)] {
+            db.record_deploy(&DeployRecord {
+                version: version.into(),
+                timestamp,
+                commit_hash: Some("abc123".into()),
+                build_time: None,
+                data_hash: Some("00ff".into()),
+            })
+            .await
+            .unwrap();
+        }
+
+        let deploys = db.deploys(1).await.unwrap();
+        assert_eq!(deploys.len(), 1);
+        assert_eq!(deploys[0].version, "0.19.0");
+        assert_eq!(deploys[0].commit_hash.as_deref(), Some("abc123"));
+        assert_eq!(deploys[0].build_time, None);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn broken_links_until_fixed() {
+        let file = TempDatabase::new("broken");
+        let db = Database::new(file.path()).await.unwrap();
+        let missing = |endpoint: &str, referrer: &str| EndpointVisit {
+            endpoint: endpoint.into(),
+            options: EndpointHistoryOptions::builder()
+                .status(404)
+                .referrer(referrer)
+                .build(),
+            count: 1,
+        };
+        db.update_endpoint_history(&[
+            missing("/blog/scraper", "/blog"),
+            missing("/blog/scraper", "example.com"),
+            missing("/old", "/links"),
+        ])
+        .await
+        .unwrap();
+        let links = db.broken_links(30).await.unwrap();
+        assert_eq!(links.len(), 3);
+        assert!(links.iter().any(|link| !link.is_internal()));
+
+        // Links to pages that exist again drop out of the report.
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        db.update_endpoint_history(&[EndpointVisit {
+            endpoint: "/old".into(),
+            options: EndpointHistoryOptions::default(),
+            count: 1,
+        }])
+        .await
+        .unwrap();
+        let endpoints: BTreeSet<String> = db
+            .broken_links(30)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|link| link.endpoint)
+            .collect();
+        assert_eq!(endpoints, BTreeSet::from(["/blog/scraper".to_string()]));
+    }
+
+    #[test]
+    fn iso_weeks() {
+        // 2027-01-01 is a Friday, which belongs to the last week of 2026.
+        assert_eq!(iso_week(1_798_761_600), "2026-W53");
+        // 2026-10-19 is the Monday starting week 43.
+        assert_eq!(iso_week(1_792_368_000), "2026-W43");
+    }
+}
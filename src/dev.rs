@@ -0,0 +1,54 @@
+//! Dev mode helpers.
+
+use super::*;
+
+/// How often to check the content directory for changes.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Watch the content directory, rebuilding the site when files change.
+pub fn watch_content(site: Site) {
+    tokio::spawn(async move {
+        let root = site.config().content_dir.clone();
+        let mut known = match PackedData::scan_directory(&root) {
+            Ok(files) => files,
+            Err(e) => {
+                tracing::error!("Unable to watch content directory: {e}");
+                return;
+            }
+        };
+
+        loop {
+            tokio::time::sleep(WATCH_INTERVAL).await;
+            let current = match PackedData::scan_directory(&root) {
+                Ok(files) => files,
+                Err(e) => {
+                    tracing::warn!("Unable to scan content directory: {e}");
+                    continue;
+                }
+            };
+
+            let changed: Vec<String> = current
+                .iter()
+                .filter(|(path, stamp)| known.get(*path) != Some(*stamp))
+                .map(|(path, _)| path.clone())
+                .collect();
+            let removed: Vec<String> = known
+                .keys()
+                .filter(|path| !current.contains_key(*path))
+                .cloned()
+                .collect();
+            known = current;
+            if changed.is_empty() && removed.is_empty() {
+                continue;
+            }
+
+            tracing::info!(
+                "Content changed ({} updated, {} removed), rebuilding.",
+                changed.len(),
+                removed.len()
+            );
+            let packed_data = site.packed_data().with_changes(&root, &changed, &removed);
+            site.reload(packed_data).await;
+        }
+    });
+}
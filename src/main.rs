@@ -1,7 +1,10 @@
 //! Main.
 
+mod analytics;
 mod cache;
+mod cli;
 mod db;
+mod dev;
 mod pages;
 mod prelude;
 mod resources;
@@ -9,14 +12,27 @@ mod site;
 mod theme;
 mod util;
 
+use clap::Parser;
 use prelude::internal::*;
 use prelude::*;
 
 /// Server entry-point.
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Build/parse site and serve.
-    let site = Site::new().await?;
-    site.serve().await?;
+    // Parse arguments.
+    let config = site::SiteConfig::parse();
+
+    // Set logging.
+    color_eyre::install()?;
+    util::init_logging(config.debug)?;
+
+    match config.command {
+        // Build/parse site and serve.
+        None | Some(cli::Command::Serve) => {
+            let site = Site::new(config).await?;
+            site.serve().await?;
+        }
+        Some(_) => cli::run(config).await?,
+    }
     Ok(())
 }
@@ -0,0 +1,485 @@
+//! Admin endpoints.
+
+use super::*;
+
+use axum::extract::Query;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+
+/// Admin pages.
+pub struct AdminPages {
+    /// Unrendered stats page.
+    stats: String,
+    /// Unrendered login page, for opening admin pages in a browser.
+    login: String,
+}
+
+impl AdminPages {
+    /// Generate new admin pages.
+    pub fn new(packed_data: Arc<PackedData>) -> Result<Self> {
+        Ok(AdminPages {
+            stats: read_page(&packed_data, "stats.html")?,
+            login: read_page(&packed_data, "login.html")?,
+        })
+    }
+}
+
+/// Configured admin token.
+/// Admin endpoints pretend not to exist when no token is configured.
+fn admin_token(site: &Site) -> Result<&str, StatusCode> {
+    match &site.config().admin_token {
+        Some(token) if !token.is_empty() => Ok(token),
+        _ => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// Check the request for a valid admin bearer token.
+fn authorize(site: &Site, headers: &HeaderMap) -> Result<(), StatusCode> {
+    let expected = admin_token(site)?;
+    let provided = headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    match util::constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+        true => Ok(()),
+        false => {
+            tracing::warn!("Rejected unauthorized admin request.");
+            Err(StatusCode::UNAUTHORIZED)
+        }
+    }
+}
+
+/// Endpoint listing page cache entries.
+pub async fn list_cache(
+    State(site): State<Site>,
+    headers: HeaderMap,
+) -> Result<axum::Json<serde_json::Value>, StatusCode> {
+    authorize(&site, &headers)?;
+    let entries = site.page_cache().entries().await;
+    let total_bytes: usize = entries.iter().map(|entry| entry.size_bytes).sum();
+    Ok(axum::Json(json!({
+        "count": entries.len(),
+        "total_bytes": total_bytes,
+        "timeout_secs": site.config().cache_timeout,
+        "entries": entries,
+    })))
+}
+
+/// Which cache entries to purge. Without either field, everything is purged.
+#[derive(Deserialize)]
+pub struct PurgeQuery {
+    /// Exact cache key (e.g., `blog/scrapers`).
+    key: Option<String>,
+    /// Key prefix (e.g., `blog/`).
+    prefix: Option<String>,
+}
+
+/// Endpoint purging page cache entries.
+pub async fn purge_cache(
+    State(site): State<Site>,
+    headers: HeaderMap,
+    Query(query): Query<PurgeQuery>,
+) -> Result<axum::Json<serde_json::Value>, StatusCode> {
+    authorize(&site, &headers)?;
+    let purged = match (&query.key, &query.prefix) {
+        (Some(_), Some(_)) => return Err(StatusCode::BAD_REQUEST),
+        (Some(key), None) => site.page_cache().purge(key).await,
+        (None, Some(prefix)) => site.page_cache().purge_prefix(prefix).await,
+        (None, None) => site.page_cache().purge_all().await,
+    };
+    tracing::info!(
+        "Purged {purged} page cache entries (key={:?}, prefix={:?}).",
+        query.key,
+        query.prefix
+    );
+    Ok(axum::Json(json!({ "purged": purged })))
+}
+
+/// Endpoint listing the latest subscriber counts reported by feed aggregators.
+pub async fn list_feed_subscribers(
+    State(site): State<Site>,
+    headers: HeaderMap,
+) -> Result<axum::Json<serde_json::Value>, StatusCode> {
+    authorize(&site, &headers)?;
+    let counts = site
+        .db()
+        .feed_subscribers(site::SUBSCRIBER_MAX_AGE_DAYS)
+        .await
+        .map_err(|e| {
+            tracing::error!("Unable to read feed subscribers: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let blog: i64 = counts
+        .iter()
+        .filter(|count| count.is_blog_feed())
+        .map(|count| count.subscribers)
+        .sum();
+    Ok(axum::Json(json!({
+        "blog_subscribers": blog,
+        "max_age_days": site::SUBSCRIBER_MAX_AGE_DAYS,
+        "feeds": counts,
+    })))
+}
+
+/// Endpoint writing a database snapshot.
+pub async fn create_backup(
+    State(site): State<Site>,
+    headers: HeaderMap,
+) -> Result<axum::Json<serde_json::Value>, StatusCode> {
+    authorize(&site, &headers)?;
+    match site.db().backup(&site.config().backup()).await {
+        Ok(path) => {
+            tracing::info!("Wrote database snapshot {}.", path.display());
+            Ok(axum::Json(json!({ "snapshot": path })))
+        }
+        Err(e) => {
+            tracing::error!("Database snapshot failed: {e}");
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Endpoint listing preview links for drafts.
+pub async fn list_drafts(
+    State(site): State<Site>,
+    headers: HeaderMap,
+) -> Result<axum::Json<serde_json::Value>, StatusCode> {
+    authorize(&site, &headers)?;
+    let secret = site.config().draft_secret.as_deref();
+    let Some(secret) = secret.filter(|s| !s.is_empty()) else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    Ok(axum::Json(json!({
+        "drafts": site.pages().blogs.draft_previews(secret),
+    })))
+}
+
+/// Links to a single missing path.
+#[derive(Debug, Serialize)]
+pub struct BrokenLinkReport {
+    /// Missing path.
+    pub endpoint: String,
+    /// Existing page the path should probably redirect to.
+    pub suggestion: Option<String>,
+    /// Total broken visits.
+    pub count: i64,
+    /// Linking pages on this site, with broken visits.
+    pub internal: Vec<(String, i64)>,
+    /// Linking sites, with broken visits.
+    pub external: Vec<(String, i64)>,
+}
+
+/// Group broken links by missing path, most followed first.
+pub fn broken_link_report(pages: &Pages, links: Vec<db::BrokenLink>) -> Vec<BrokenLinkReport> {
+    let mut reports: Vec<BrokenLinkReport> = Vec::new();
+    for link in links {
+        let index = match reports
+            .iter()
+            .position(|report| report.endpoint == link.endpoint)
+        {
+            Some(index) => index,
+            None => {
+                reports.push(BrokenLinkReport {
+                    suggestion: pages.suggest_redirect(&link.endpoint),
+                    endpoint: link.endpoint.clone(),
+                    count: 0,
+                    internal: Vec::new(),
+                    external: Vec::new(),
+                });
+                reports.len() - 1
+            }
+        };
+        let report = &mut reports[index];
+        report.count += link.count;
+        match link.is_internal() {
+            true => report.internal.push((link.referrer, link.count)),
+            false => report.external.push((link.referrer, link.count)),
+        }
+    }
+    reports.sort_by_key(|report| std::cmp::Reverse(report.count));
+    reports
+}
+
+/// Broken link report options.
+#[derive(Deserialize)]
+pub struct BrokenLinksQuery {
+    /// Number of days to include.
+    days: Option<u32>,
+}
+
+/// Endpoint listing links to missing pages.
+pub async fn list_broken_links(
+    State(site): State<Site>,
+    headers: HeaderMap,
+    Query(query): Query<BrokenLinksQuery>,
+) -> Result<axum::Json<serde_json::Value>, StatusCode> {
+    authorize(&site, &headers)?;
+    let days = query.days.unwrap_or(STATS_DEFAULT_DAYS);
+    let links = site.db().broken_links(days).await.map_err(|e| {
+        tracing::error!("Unable to read broken links: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let reports = broken_link_report(&site.pages(), links);
+    Ok(axum::Json(json!({
+        "days": days,
+        "broken_links": reports,
+    })))
+}
+
+/// Stats page options.
+#[derive(Deserialize)]
+pub struct StatsQuery {
+    /// Number of days to show.
+    days: Option<u32>,
+}
+
+/// Number of days shown on the stats page by default.
+const STATS_DEFAULT_DAYS: u32 = 30;
+
+/// Number of rows in each ranking on the stats page.
+const STATS_TOP: usize = 20;
+
+/// Cookie holding a browser admin session.
+const SESSION_COOKIE: &str = "hachha_admin";
+
+/// How long a browser admin session lasts.
+const SESSION_LIFETIME: Duration = Duration::from_secs(12 * 60 * 60);
+
+/// Session cookie value expiring at `expires` (unix timestamp), signed with
+/// the admin token so it never has to be stored in the browser.
+fn session_value(token: &str, expires: i64) -> Option<String> {
+    let signature = util::hmac_hex(token, &format!("admin-session:{expires}"))?;
+    Some(format!("{expires}.{signature}"))
+}
+
+/// Whether a session cookie value is signed with the admin token and hasn't
+/// expired.
+fn session_valid(token: &str, value: &str, now: i64) -> bool {
+    let Some(expires) = value
+        .split_once('.')
+        .and_then(|(expires, _)| expires.parse::<i64>().ok())
+    else {
+        return false;
+    };
+    expires > now
+        && session_value(token, expires)
+            .is_some_and(|expected| util::constant_time_eq(value.as_bytes(), expected.as_bytes()))
+}
+
+/// Check the request for a valid admin bearer token or, for pages opened in a
+/// browser, a session cookie from [`admin_login`].
+fn authorize_browser(site: &Site, headers: &HeaderMap) -> Result<(), StatusCode> {
+    if headers.contains_key(axum::http::header::AUTHORIZATION) {
+        return authorize(site, headers);
+    }
+    let expected = admin_token(site)?;
+    let now = chrono::Utc::now().timestamp();
+    let session = headers
+        .get_all(axum::http::header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .filter_map(|cookie| {
+            cookie
+                .trim()
+                .strip_prefix(SESSION_COOKIE)?
+                .strip_prefix('=')
+        })
+        .any(|value| session_valid(expected, value, now));
+    match session {
+        true => Ok(()),
+        false => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+/// Render the admin login page.
+fn login_page(site: &Site, status: StatusCode, failed: bool) -> axum::response::Response {
+    (
+        status,
+        [
+            (axum::http::header::CACHE_CONTROL, "no-store"),
+            (
+                axum::http::HeaderName::from_static("x-robots-tag"),
+                "noindex",
+            ),
+        ],
+        site.render_page(&site.pages().admin.login, &json!({ "failed": failed })),
+    )
+        .into_response()
+}
+
+/// Admin login form.
+#[derive(Deserialize)]
+pub struct LoginForm {
+    token: String,
+}
+
+/// Endpoint exchanging the admin token for a session cookie, so admin pages
+/// can be opened in a browser.
+pub async fn admin_login(
+    State(site): State<Site>,
+    axum::Form(form): axum::Form<LoginForm>,
+) -> Result<axum::response::Response, StatusCode> {
+    let expected = admin_token(&site)?;
+    if !util::constant_time_eq(form.token.as_bytes(), expected.as_bytes()) {
+        tracing::warn!("Rejected admin login.");
+        return Ok(login_page(&site, StatusCode::UNAUTHORIZED, true));
+    }
+
+    let lifetime = SESSION_LIFETIME.as_secs();
+    let expires = chrono::Utc::now().timestamp() + lifetime as i64;
+    let Some(value) = session_value(expected, expires) else {
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    };
+    let cookie = format!(
+        "{SESSION_COOKIE}={value}; Path=/admin; Max-Age={lifetime}; HttpOnly; Secure; SameSite=Strict"
+    );
+    Ok((
+        StatusCode::SEE_OTHER,
+        [
+            (axum::http::header::LOCATION, "/admin/stats".to_string()),
+            (axum::http::header::SET_COOKIE, cookie),
+        ],
+    )
+        .into_response())
+}
+
+/// Endpoint for the stats dashboard. Browsers without a session are shown the
+/// login page.
+pub async fn visit_stats(
+    State(site): State<Site>,
+    headers: HeaderMap,
+    Query(query): Query<StatsQuery>,
+) -> Result<axum::response::Response, StatusCode> {
+    match authorize_browser(&site, &headers) {
+        Ok(()) => {}
+        Err(StatusCode::UNAUTHORIZED) => {
+            return Ok(login_page(&site, StatusCode::UNAUTHORIZED, false))
+        }
+        Err(status) => return Err(status),
+    }
+    let days = query
+        .days
+        .unwrap_or(STATS_DEFAULT_DAYS)
+        .clamp(1, site.config().daily_hits_retention_days.max(1));
+    let context = stats_context(&site, days).await.map_err(|e| {
+        tracing::error!("Unable to collect stats: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok((
+        [
+            (axum::http::header::CACHE_CONTROL, "no-store"),
+            (
+                axum::http::HeaderName::from_static("x-robots-tag"),
+                "noindex",
+            ),
+        ],
+        site.render_page(&site.pages().admin.stats, &context),
+    )
+        .into_response())
+}
+
+/// Collect the stats page context for the last `days`.
+async fn stats_context(site: &Site, days: u32) -> Result<serde_json::Value> {
+    let db = site.db();
+    let top_pages = db.top_endpoints(days, 200..=299, STATS_TOP).await?;
+    let top_missing = db.top_endpoints(days, 404..=404, STATS_TOP).await?;
+    let subscribers = db
+        .blog_feed_subscribers(site::SUBSCRIBER_MAX_AGE_DAYS)
+        .await?;
+    let deploys = db.deploys(STATS_TOP).await?;
+    let deploy_days: BTreeSet<String> = deploys
+        .iter()
+        .filter_map(|deploy| chrono::DateTime::from_timestamp(deploy.timestamp, 0))
+        .map(|time| time.date_naive().to_string())
+        .collect();
+
+    // Fill in days without visits so every post shares the same timeline.
+    let today = chrono::Utc::now().date_naive();
+    let timeline: Vec<chrono::NaiveDate> = (0..days)
+        .rev()
+        .filter_map(|offset| today.checked_sub_days(chrono::Days::new(offset.into())))
+        .collect();
+    let post_hits = db.daily_hits_by_route("/blog/{path}", days).await?;
+    let busiest_day = post_hits
+        .values()
+        .flat_map(|hits| hits.values())
+        .copied()
+        .max()
+        .unwrap_or_default()
+        .max(1);
+    let mut posts: Vec<(i64, serde_json::Value)> = post_hits
+        .iter()
+        .map(|(endpoint, hits)| {
+            let bars: Vec<serde_json::Value> = timeline
+                .iter()
+                .map(|day| {
+                    let timestamp = day
+                        .and_time(chrono::NaiveTime::default())
+                        .and_utc()
+                        .timestamp();
+                    let count = hits.get(&timestamp).copied().unwrap_or_default();
+                    json!({
+                        "day": day.to_string(),
+                        "count": count,
+                        "height": count * 100 / busiest_day,
+                        "deploy": deploy_days.contains(&day.to_string()),
+                    })
+                })
+                .collect();
+            let total: i64 = hits.values().sum();
+            (
+                total,
+                json!({ "endpoint": endpoint, "total": total, "days": bars }),
+            )
+        })
+        .collect();
+    posts.sort_by_key(|post| std::cmp::Reverse(post.0));
+
+    Ok(json!({
+        "days": days,
+        "ranges": [7, 30, 90],
+        "top_pages": top_pages,
+        "top_missing": top_missing,
+        "posts": posts.into_iter().map(|(_, post)| post).collect::<Vec<_>>(),
+        "subscribers": subscribers,
+        "deploys": deploys
+            .iter()
+            .map(|deploy| {
+                let mut context = util::to_json(deploy)?;
+                context["date"] = json!(format_timestamp(deploy.timestamp));
+                Ok(context)
+            })
+            .collect::<Result<Vec<_>>>()?,
+        "running": util::to_json(&meta::VersionData::default())?,
+    }))
+}
+
+/// Format a unix timestamp as a UTC date and time.
+fn format_timestamp(timestamp: i64) -> String {
+    match chrono::DateTime::from_timestamp(timestamp, 0) {
+        Some(time) => time.format("%Y-%m-%d %H:%M UTC").to_string(),
+        None => timestamp.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pages::admin::*;
+
+    #[test]
+    fn admin_sessions() {
+        let value = session_value("token", 200).unwrap();
+        assert!(session_valid("token", &value, 100));
+
+        // Sessions expire, and only the admin token can sign them.
+        assert!(!session_valid("token", &value, 200));
+        assert!(!session_valid("other", &value, 100));
+        let forged = value.replacen("200", "900", 1);
+        assert!(!session_valid("token", &forged, 100));
+        assert!(!session_valid("token", "token", 100));
+        assert!(!session_valid("token", "", 100));
+    }
+}
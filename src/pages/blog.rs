@@ -2,24 +2,44 @@
 
 use super::*;
 
-use atom_syndication as atom;
+use axum::extract::Query;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
 
 /// The blogs page and subpages.
 pub struct BlogsPages {
     index: String,
     post: String,
+    archive: String,
+    series: String,
     blogs: Blogs,
-    feed: String,
+    /// Unpublished and scheduled posts, only reachable through preview links.
+    drafts: Blogs,
+    feed: feed::Feed,
+    /// Feeds by tag.
+    tag_feeds: BTreeMap<String, feed::Feed>,
     metadata: serde_json::Value,
+    /// Series page metadata by series slug.
+    series_metadata: BTreeMap<String, serde_json::Value>,
+    /// Posts that failed to parse.
+    pub errors: Vec<String>,
+    /// When the next scheduled post is due.
+    next_scheduled: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 impl BlogsPages {
     /// Generate new blogs pages.
     pub fn new(packed_data: Arc<PackedData>) -> Result<Self> {
         // Parse pages.
-        let blogs_template = util::read_embedded_text::<EmbeddedPages>("blogs.html")?;
-        let post_template = util::read_embedded_text::<EmbeddedPages>("post.html")?;
+        let blogs_template = read_page(&packed_data, "blogs.html")?;
+        let post_template = read_page(&packed_data, "post.html")?;
+        let archive_template = read_page(&packed_data, "archive.html")?;
+        let series_template = read_page(&packed_data, "series.html")?;
         let mut blogs = Blogs::default();
+        let mut drafts = Blogs::default();
+        let mut errors = Vec::new();
+        let mut next_scheduled: Option<chrono::DateTime<chrono::Utc>> = None;
+        let now = chrono::Utc::now();
         for (path, _data) in packed_data.iter() {
             if !(path.starts_with("content/posts") && path.ends_with(".md")) {
                 continue;
@@ -48,10 +68,19 @@ impl BlogsPages {
                     }
 
                     blog.markdown = markdown.to_string();
+                    blog.updated = blog.updated.filter(|updated| updated.time > blog.date.time);
                     blog.metadata = util::to_json(&blog)?;
 
                     if blog.unpublished {
                         tracing::debug!("Skipping {}: unpublished.", blog.name);
+                        drafts.push(blog);
+                        continue;
+                    }
+                    if blog.date.time > now {
+                        tracing::debug!("Skipping {}: scheduled for {}.", blog.name, blog.date);
+                        let due = blog.date.time.to_utc();
+                        next_scheduled = Some(next_scheduled.map_or(due, |next| next.min(due)));
+                        drafts.push(blog);
                         continue;
                     }
 
@@ -59,6 +88,7 @@ impl BlogsPages {
                 }
                 Err(e) => {
                     tracing::error!("Failed to parse blog ({path}): {e}");
+                    errors.push(format!("Failed to parse blog ({path}): {e}"));
                 }
             };
         }
@@ -66,50 +96,43 @@ impl BlogsPages {
         blogs.sort();
         blogs.reverse();
 
-        // Parse into atom feed.
-        let mut feed_builder = atom::FeedBuilder::default();
-        feed_builder
-            .title("hachha.dev")
-            .author(atom::PersonBuilder::default().name("Harrison Hall").build())
-            .link(
-                atom::LinkBuilder::default()
-                    .href("https://hachha.dev")
-                    .title("hachha.dev".to_string())
-                    .build(),
-            )
-            .icon("https://hachha.dev/media/catman.png".to_string())
-            .subtitle(Some("hachha.dev blog feed".to_string().into()));
-        let mut entries = Vec::new();
-        for blog in blogs.iter() {
-            let timestamp: chrono::DateTime<chrono::FixedOffset> = blog
-                .date
-                .and_time(chrono::NaiveTime::default())
-                .and_local_timezone(chrono::Utc)
-                .latest()
-                .ok_or(anyhow!("Unable to convert dt {} to utc.", blog.date))?
-                .into();
-            let entry = atom::EntryBuilder::default()
-                .title(blog.name.clone())
-                .summary(Some(blog.blurb.clone().into()))
-                .link(
-                    atom::LinkBuilder::default()
-                        .href(format!("https://hachha.dev/blog/{}", blog.uri.as_str()))
-                        .title(blog.name.clone())
-                        .build(),
-                )
-                .published(timestamp)
-                .updated(timestamp)
-                .categories(
-                    blog.tags
-                        .iter()
-                        .map(|tag| atom::CategoryBuilder::default().term(tag).build())
-                        .collect::<Vec<atom::Category>>(),
-                )
-                .build();
-            entries.push(entry);
+        // Link posts in the same series to each other, and to their
+        // neighbours and related posts.
+        let series_metadata = link_series(&mut blogs)?;
+        link_related(&mut blogs);
+
+        // Parse into feeds, one for all posts and one per tag.
+        let items: Vec<feed::Item> = blogs.iter().map(Blog::feed_item).collect();
+        let feed = feed::Feed::new(
+            &feed::Channel {
+                title: "hachha.dev".into(),
+                subtitle: "hachha.dev blog feed".into(),
+                link: "https://hachha.dev".into(),
+                json_url: "https://hachha.dev/blog.json".into(),
+            },
+            &items,
+        )?;
+        let mut tag_feeds = BTreeMap::new();
+        for tag in blogs.iter().flat_map(|blog| blog.tags.iter()) {
+            if tag_feeds.contains_key(tag) {
+                continue;
+            }
+            let tag_items: Vec<feed::Item> = items
+                .iter()
+                .filter(|item| item.tags.contains(tag))
+                .cloned()
+                .collect();
+            let tag_feed = feed::Feed::new(
+                &feed::Channel {
+                    title: format!("hachha.dev: {tag}"),
+                    subtitle: format!("hachha.dev blog feed for posts tagged {tag}"),
+                    link: format!("https://hachha.dev/blog/tag/{tag}"),
+                    json_url: format!("https://hachha.dev/blog/tag/{tag}.json"),
+                },
+                &tag_items,
+            )?;
+            tag_feeds.insert(tag.clone(), tag_feed);
         }
-        feed_builder.entries(entries);
-        let feed = feed_builder.build().to_string();
 
         // Generate blog metadata. Darken every other entry.
         let mut metadata = json!({});
@@ -137,18 +160,134 @@ impl BlogsPages {
         Ok(BlogsPages {
             index: blogs_template,
             post: post_template,
+            archive: archive_template,
+            series: series_template,
             blogs,
+            drafts,
             feed,
+            tag_feeds,
             metadata,
+            series_metadata,
+            errors,
+            next_scheduled,
         })
     }
 
+    /// Preview links for every draft.
+    pub fn draft_previews(&self, secret: &str) -> Vec<DraftPreview> {
+        self.drafts
+            .iter()
+            // Posts without a uri can't be routed to.
+            .filter(|draft| !draft.uri.is_empty())
+            .filter_map(|draft| {
+                Some(DraftPreview {
+                    name: draft.name.clone(),
+                    date: draft.date.to_string(),
+                    scheduled: !draft.unpublished,
+                    preview: format!(
+                        "/blog/draft/{}?token={}",
+                        draft.uri,
+                        draft_token(secret, &draft.uri)?
+                    ),
+                })
+            })
+            .collect()
+    }
+
+    /// Whether a scheduled post should be published by now.
+    fn scheduled_post_due(&self) -> bool {
+        self.next_scheduled
+            .is_some_and(|due| due <= chrono::Utc::now())
+    }
+
+    /// Paths of every post and tag page.
+    pub fn paths(&self) -> Vec<String> {
+        let mut tags = BTreeSet::new();
+        let mut paths = Vec::new();
+        for blog in self.blogs.iter() {
+            paths.push(format!("/blog/{}", blog.uri));
+            tags.extend(blog.tags.iter());
+        }
+        paths.extend(tags.into_iter().map(|tag| format!("/blog/tag/{tag}")));
+        paths.extend(
+            self.series_metadata
+                .keys()
+                .map(|series| format!("/blog/series/{series}")),
+        );
+        paths.push("/blog/archive".into());
+        paths
+    }
+
     fn get_blog(&self, path: &str) -> Option<&Blog> {
         self.blogs
             .iter()
             .find(|&other_blog| path == other_blog.uri)
             .map(|v| v as _)
     }
+
+    /// Archive metadata for posts in `year` and `month`, if any. Posts are
+    /// grouped by year, then by month, newest first.
+    fn archive_metadata(&self, year: Option<i32>, month: Option<u32>) -> Option<serde_json::Value> {
+        let mut years: Vec<serde_json::Value> = Vec::new();
+        let mut count = 0;
+        for blog in self.blogs.iter() {
+            if year.is_some_and(|year| blog.date.year() != year)
+                || month.is_some_and(|month| blog.date.month() != month)
+            {
+                continue;
+            }
+            count += 1;
+
+            if years
+                .last()
+                .is_none_or(|last| last["year"] != blog.date.year())
+            {
+                years.push(json!({
+                    "year": blog.date.year(),
+                    "path": format!("/blog/{}", blog.date.year()),
+                    "count": 0,
+                    "months": [],
+                }));
+            }
+            let last_year = years.last_mut()?;
+            last_year["count"] = json!(last_year["count"].as_u64().unwrap_or_default() + 1);
+
+            let months = last_year["months"].as_array_mut()?;
+            if months
+                .last()
+                .is_none_or(|last| last["month"] != blog.date.month())
+            {
+                months.push(json!({
+                    "month": blog.date.month(),
+                    "name": blog.date.format("%B").to_string(),
+                    "path": blog.date.format("/blog/%Y/%m").to_string(),
+                    "count": 0,
+                    "blogs": [],
+                }));
+            }
+            let last_month = months.last_mut()?;
+            last_month["count"] = json!(last_month["count"].as_u64().unwrap_or_default() + 1);
+            let mut meta = blog.metadata.clone();
+            util::merge_json(&mut meta, &json!({"path": blog.uri})).ok()?;
+            last_month["blogs"].as_array_mut()?.push(meta);
+        }
+        if count == 0 {
+            return None;
+        }
+
+        let period = match (year, month) {
+            (Some(year), Some(month)) => chrono::NaiveDate::from_ymd_opt(year, month, 1)?
+                .format("%B %Y")
+                .to_string(),
+            (Some(year), None) => year.to_string(),
+            _ => String::new(),
+        };
+        Some(json!({
+            "period": period,
+            "count": count,
+            "years": years,
+        }))
+    }
 }
 
 /// Parsed blog list.
@@ -179,8 +318,12 @@ pub struct Blog {
     name: String,
     /// Description of blog.
     blurb: String,
-    /// Date blog was written.
-    date: chrono::NaiveDate,
+    /// Date blog was written, and when it's published.
+    date: PostDate,
+    /// Date blog was last edited, if after it was published. Parsed like
+    /// `date`, so a bare date is midnight UTC.
+    #[serde(default)]
+    updated: Option<PostDate>,
     /// Relative URI for blog.
     #[serde(alias = "article", alias = "post")]
     uri: String,
@@ -190,6 +333,12 @@ pub struct Blog {
     /// Tags.
     #[serde(default)]
     tags: BTreeSet<String>,
+    /// Name of the series this post is part of.
+    #[serde(default)]
+    series: Option<String>,
+    /// Position in the series. Parts without one follow, by date.
+    #[serde(default)]
+    series_part: Option<u32>,
     /// Read markdown of blog entry.
     #[serde(skip)]
     markdown: String,
@@ -205,6 +354,22 @@ impl Blog {
     fn default_unpublished() -> bool {
         false
     }
+
+    /// Feed entry for this post, with the rendered post as content.
+    fn feed_item(&self) -> feed::Item {
+        feed::Item {
+            title: self.name.clone(),
+            link: format!("https://hachha.dev/blog/{}", self.uri),
+            summary: self.blurb.clone(),
+            content: Some(util::absolute_urls(
+                &util::markdown_to_html(&self.markdown),
+                "https://hachha.dev",
+            )),
+            published: self.date.time,
+            updated: self.updated.unwrap_or(self.date).time,
+            tags: self.tags.iter().cloned().collect(),
+        }
+    }
 }
 
 impl Default for Blog {
@@ -212,10 +377,13 @@ impl Default for Blog {
         Blog {
             name: "".to_string(),
             blurb: "".to_string(),
-            date: chrono::NaiveDate::default(),
+            date: PostDate::default(),
+            updated: None,
             uri: "".to_string(),
             directory: "".into(),
             tags: BTreeSet::new(),
+            series: None,
+            series_part: None,
             markdown: "".to_string(),
             metadata: json!({}),
             unpublished: false,
@@ -235,143 +403,869 @@ impl std::cmp::Ord for Blog {
     }
 }
 
+/// Set the series navigation of every post in a series, and collect the
+/// series pages.
+fn link_series(blogs: &mut Blogs) -> Result<BTreeMap<String, serde_json::Value>> {
+    let mut series: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (i, blog) in blogs.iter().enumerate() {
+        if let Some(name) = &blog.series {
+            series.entry(series_slug(name)).or_default().push(i);
+        }
+    }
+
+    let mut series_metadata = BTreeMap::new();
+    for (slug, mut parts) in series {
+        parts.sort_by_key(|&i| (blogs[i].series_part.unwrap_or(u32::MAX), blogs[i].date));
+        let name = blogs[parts[0]].series.clone().unwrap_or_default();
+        let path = format!("/blog/series/{slug}");
+        let link = |i: usize| json!({"name": blogs[i].name, "path": blogs[i].uri});
+
+        let navigation: Vec<serde_json::Value> = (0..parts.len())
+            .map(|index| {
+                json!({
+                    "name": name,
+                    "path": path,
+                    "part": index + 1,
+                    "parts": parts.len(),
+                    "prev": index.checked_sub(1).map(|prev| link(parts[prev])),
+                    "next": parts.get(index + 1).map(|&next| link(next)),
+                })
+            })
+            .collect();
+        let mut part_metadata = Vec::new();
+        for (&i, navigation) in parts.iter().zip(navigation) {
+            let blog = &mut blogs[i];
+            blog.metadata["series_nav"] = navigation;
+            let mut meta = blog.metadata.clone();
+            util::merge_json(
+                &mut meta,
+                &json!({"darken": part_metadata.len().is_multiple_of(2), "path": blog.uri}),
+            )?;
+            part_metadata.push(meta);
+        }
+
+        series_metadata.insert(
+            slug,
+            json!({
+                "series": name,
+                "path": path,
+                "parts": part_metadata.len(),
+                "blogs": part_metadata,
+            }),
+        );
+    }
+
+    Ok(series_metadata)
+}
+
+/// Number of related posts shown under a post.
+const RELATED_POSTS: usize = 3;
+
+/// Set the chronological neighbours and related posts of every post. Posts are
+/// related by shared tags, with similar wording breaking ties.
+fn link_related(blogs: &mut Blogs) {
+    let words: Vec<BTreeSet<String>> = blogs
+        .iter()
+        .map(|blog| post_words(&blog.markdown))
+        .collect();
+    let link = |blog: &Blog| json!({"name": blog.name, "path": blog.uri, "date": blog.date});
+
+    let mut context = Vec::new();
+    for (i, blog) in blogs.iter().enumerate() {
+        let mut related: Vec<(f64, usize)> = blogs
+            .iter()
+            .enumerate()
+            .filter(|(j, other)| *j != i && (blog.series.is_none() || other.series != blog.series))
+            .map(|(j, other)| {
+                let shared_tags = blog.tags.intersection(&other.tags).count();
+                (shared_tags as f64 + similarity(&words[i], &words[j]), j)
+            })
+            .filter(|(score, _)| *score >= 1.0)
+            .collect();
+        related.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+
+        // Posts are sorted newest first.
+        context.push(json!({
+            "post_nav": {
+                "prev": blogs.get(i + 1).map(link),
+                "next": i.checked_sub(1).map(|newer| link(&blogs[newer])),
+            },
+            "related": related
+                .iter()
+                .take(RELATED_POSTS)
+                .map(|(_, j)| link(&blogs[*j]))
+                .collect::<Vec<_>>(),
+        }));
+    }
+    for (blog, context) in blogs.iter_mut().zip(context) {
+        blog.metadata["post_nav"] = context["post_nav"].clone();
+        blog.metadata["related"] = context["related"].clone();
+    }
+}
+
+/// Distinct words of a post worth comparing, skipping short filler words.
+fn post_words(markdown: &str) -> BTreeSet<String> {
+    markdown
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() > 4)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Share of words two posts have in common, from 0 to 1.
+fn similarity(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f64 {
+    let union = a.union(b).count();
+    match union {
+        0 => 0.0,
+        union => a.intersection(b).count() as f64 / union as f64,
+    }
+}
+
+/// Url-safe name of a series (e.g., `Rust & Nix` becomes `rust-nix`).
+fn series_slug(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Post date, optionally with a time and timezone (e.g., `2026-03-01`,
+/// `2026-03-01 09:30` or `2026-03-01T09:30:00+09:00`). Posts without a time
+/// are published at midnight, and times without a timezone are UTC.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PostDate {
+    /// Publication time.
+    time: chrono::DateTime<chrono::FixedOffset>,
+    /// Calendar date in the post's timezone.
+    date: chrono::NaiveDate,
+}
+
+impl From<chrono::DateTime<chrono::FixedOffset>> for PostDate {
+    fn from(time: chrono::DateTime<chrono::FixedOffset>) -> Self {
+        PostDate {
+            time,
+            date: time.date_naive(),
+        }
+    }
+}
+
+impl Default for PostDate {
+    fn default() -> Self {
+        feed::midnight(chrono::NaiveDate::default()).into()
+    }
+}
+
+impl std::ops::Deref for PostDate {
+    type Target = chrono::NaiveDate;
+    fn deref(&self) -> &Self::Target {
+        &self.date
+    }
+}
+
+impl std::fmt::Display for PostDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.time.to_rfc3339())
+    }
+}
+
+impl std::str::FromStr for PostDate {
+    type Err = chrono::ParseError;
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        let value = value.trim().replacen(' ', "T", 1);
+        if let Ok(date) = chrono::NaiveDate::parse_from_str(&value, "%Y-%m-%d") {
+            return Ok(feed::midnight(date).into());
+        }
+        for format in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"] {
+            if let Ok(time) = chrono::NaiveDateTime::parse_from_str(&value, format) {
+                return Ok(time.and_utc().fixed_offset().into());
+            }
+        }
+        if let Ok(time) = chrono::DateTime::parse_from_str(&value, "%Y-%m-%dT%H:%M%:z") {
+            return Ok(time.into());
+        }
+        Ok(chrono::DateTime::parse_from_rfc3339(&value)?.into())
+    }
+}
+
+impl TryFrom<String> for PostDate {
+    type Error = chrono::ParseError;
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<PostDate> for String {
+    fn from(date: PostDate) -> Self {
+        date.date.to_string()
+    }
+}
+
+/// Preview link for a draft.
+#[derive(Debug, Serialize)]
+pub struct DraftPreview {
+    /// Title of the draft.
+    name: String,
+    /// Post date, with time and timezone.
+    date: String,
+    /// Whether the draft is published automatically at `date`.
+    scheduled: bool,
+    /// Preview path, including the token.
+    preview: String,
+}
+
+/// Preview token for the draft at `uri`, derived from the draft secret. An
+/// empty secret yields no token.
+fn draft_token(secret: &str, uri: &str) -> Option<String> {
+    if secret.is_empty() {
+        return None;
+    }
+    util::hmac_hex(secret, uri)
+}
+
+/// Longest wait between checks for scheduled posts, so posts added by a
+/// content reload are picked up too.
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Spawn a task rebuilding pages whenever a scheduled post is due.
+pub fn spawn_scheduled_publishing(site: Site) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let wait = match site.pages().blogs.next_scheduled {
+                Some(due) => (due - chrono::Utc::now())
+                    .to_std()
+                    .unwrap_or_default()
+                    .min(SCHEDULE_CHECK_INTERVAL),
+                None => SCHEDULE_CHECK_INTERVAL,
+            };
+            tokio::time::sleep(wait).await;
+            if !site.pages().blogs.scheduled_post_due() {
+                continue;
+            }
+
+            tracing::info!("Publishing scheduled posts.");
+            site.reload(site.packed_data().as_ref().clone()).await;
+            // Don't retry immediately if the rebuild failed.
+            if site.pages().blogs.scheduled_post_due() {
+                tracing::warn!("Scheduled posts are still unpublished after rebuilding.");
+                tokio::time::sleep(SCHEDULE_CHECK_INTERVAL).await;
+            }
+        }
+    })
+}
+
+/// Blog list options.
+#[derive(Default, Deserialize)]
+pub struct PageQuery {
+    /// Page number, starting at 1. Kept as text so invalid numbers get a 404
+    /// like out of range pages rather than a query rejection.
+    page: Option<String>,
+}
+
+impl PageQuery {
+    /// Requested page, or `None` if it isn't a page number.
+    fn page(&self) -> Option<usize> {
+        match &self.page {
+            Some(page) => page.parse().ok(),
+            None => Some(1),
+        }
+    }
+}
+
 /// Endpoint for blogs index page.
-pub async fn visit_blog_index(uri: Uri, State(site): State<Site>) -> RenderedHtml {
-    EndpointHistoryOptions::default()
-        .write(&site, uri.path())
-        .await;
+pub async fn visit_blog_index(
+    State(site): State<Site>,
+    Query(query): Query<PageQuery>,
+) -> axum::response::Response {
+    let posts = site.pages().blogs.metadata["blogs"]
+        .as_array()
+        .map_or(0, Vec::len);
+    let Some(page) = query
+        .page()
+        .filter(|page| page_exists(posts, *page, site.config().blog_page_size))
+    else {
+        let page = query.page.unwrap_or_default();
+        return error::visit_404_internal(format!("/blog?page={page}"), State(site), None)
+            .await
+            .into_response();
+    };
+
     site.clone()
         .page_cache()
-        .retrieve_or_update("blog", async move {
-            site.render_page(&site.pages().blogs.index, &site.pages().blogs.metadata)
+        .retrieve_or_update(&format!("blog?page={page}"), async move {
+            let mut metadata = site.pages().blogs.metadata.clone();
+            paginate(&mut metadata, "/blog", page, site.config().blog_page_size);
+            site.render_page(&site.pages().blogs.index, &metadata)
         })
         .await
+        .into_response()
+}
+
+/// Limit listed posts to a single page, adding links to the neighbouring
+/// pages. Callers check the page exists with [`page_exists`] first.
+fn paginate(metadata: &mut serde_json::Value, base: &str, page: usize, page_size: usize) {
+    let Some(blogs) = metadata["blogs"].as_array_mut() else {
+        return;
+    };
+    if page_size == 0 {
+        return;
+    }
+    let pages = blogs.len().div_ceil(page_size).max(1);
+
+    *blogs = blogs
+        .drain(..)
+        .skip((page - 1) * page_size)
+        .take(page_size)
+        .collect();
+    if pages > 1 {
+        let link = |page: usize| match page {
+            1 => base.to_string(),
+            page => format!("{base}?page={page}"),
+        };
+        metadata["pagination"] = json!({
+            "page": page,
+            "pages": pages,
+            "prev": (page > 1).then(|| link(page - 1)),
+            "next": (page < pages).then(|| link(page + 1)),
+        });
+    }
+}
+
+/// Whether a listing of `posts` has the given page.
+fn page_exists(posts: usize, page: usize, page_size: usize) -> bool {
+    match page_size {
+        0 => page == 1,
+        page_size => (1..=posts.div_ceil(page_size).max(1)).contains(&page),
+    }
 }
 
 /// Endpoint for individual blogs.
 pub async fn visit_blog(
-    uri: Uri,
     Path(blog): Path<String>,
     State(site): State<Site>,
     headers: HeaderMap,
-) -> RenderedHtml {
+) -> axum::response::Response {
     // Visit index
     if blog.is_empty() {
-        return visit_blog_index(uri, State(site)).await;
+        return visit_blog_index(State(site), Query(PageQuery::default())).await;
+    }
+
+    // Posts take priority over year archives.
+    if site.pages().blogs.get_blog(&blog).is_none() {
+        if let Some(year) = parse_year(&blog) {
+            return visit_archive_internal(Some(year), None, State(site)).await;
+        }
     }
 
     // Check for blog.
     let full_blog_path: String = format!("blog/{blog}");
     match site.pages().blogs.get_blog(&blog) {
-        Some(blog) => {
-            EndpointHistoryOptions::default()
-                .write(&site, uri.path())
-                .await;
-            site.clone()
-                .page_cache()
-                .retrieve_or_update(&full_blog_path, async move {
-                    let mut blog_metadata = blog.metadata.clone();
-                    blog_metadata["blog-content"] =
-                        serde_json::Value::String(blog.markdown.clone());
-                    site.render_page(&site.pages().blogs.post, &blog_metadata)
-                })
-                .await
-        }
-        None => {
-            EndpointHistoryOptions::builder()
-                .valid(true)
-                .build()
-                .write(&site, uri.path())
-                .await;
-            error::visit_404_internal(format!("/blog/{blog}"), State(site), Some(headers)).await
-        }
+        Some(blog) => site
+            .clone()
+            .page_cache()
+            .retrieve_or_update(&full_blog_path, async move {
+                let mut blog_metadata = blog.metadata.clone();
+                blog_metadata["blog-content"] = serde_json::Value::String(blog.markdown.clone());
+                site.render_page(&site.pages().blogs.post, &blog_metadata)
+            })
+            .await
+            .into_response(),
+        None => error::visit_404_internal(format!("/blog/{blog}"), State(site), Some(headers))
+            .await
+            .into_response(),
     }
 }
 
+/// Endpoint for the index of a series.
+pub async fn visit_series(
+    Path(series): Path<String>,
+    State(site): State<Site>,
+) -> axum::response::Response {
+    let Some(metadata) = site.pages().blogs.series_metadata.get(&series).cloned() else {
+        return error::visit_404_internal(format!("/blog/series/{series}"), State(site), None)
+            .await
+            .into_response();
+    };
+
+    site.clone()
+        .page_cache()
+        .retrieve_or_update(&format!("series/{series}"), async move {
+            site.render_page(&site.pages().blogs.series, &metadata)
+        })
+        .await
+        .into_response()
+}
+
+/// Draft preview options.
+#[derive(Deserialize)]
+pub struct DraftQuery {
+    /// Preview token for the draft.
+    token: Option<String>,
+}
+
+/// Endpoint for draft previews. Drafts pretend not to exist without a valid
+/// token, and are never cached or indexed.
+pub async fn visit_draft(
+    Path(uri): Path<String>,
+    State(site): State<Site>,
+    Query(query): Query<DraftQuery>,
+) -> axum::response::Response {
+    let secret = site.config().draft_secret.as_deref();
+    let draft = secret.filter(|s| !s.is_empty()).and_then(|secret| {
+        let expected = draft_token(secret, &uri)?;
+        let provided = query.token.as_deref().unwrap_or_default();
+        util::constant_time_eq(provided.as_bytes(), expected.as_bytes())
+            .then(|| {
+                site.pages()
+                    .blogs
+                    .drafts
+                    .iter()
+                    .find(|draft| draft.uri == uri)
+                    .cloned()
+            })
+            .flatten()
+    });
+    let Some(draft) = draft else {
+        return error::visit_404_internal(format!("/blog/draft/{uri}"), State(site), None)
+            .await
+            .into_response();
+    };
+
+    let mut metadata = draft.metadata.clone();
+    metadata["blog-content"] = serde_json::Value::String(draft.markdown.clone());
+    metadata["draft"] = serde_json::Value::Bool(true);
+    (
+        [
+            (axum::http::header::CACHE_CONTROL, "no-store"),
+            (
+                axum::http::HeaderName::from_static("x-robots-tag"),
+                "noindex",
+            ),
+        ],
+        site.render_page(&site.pages().blogs.post, &metadata),
+    )
+        .into_response()
+}
+
 /// Visit tag.
 pub async fn visit_tag(
-    uri: Uri,
     Path(tag): Path<String>,
     State(site): State<Site>,
-) -> RenderedHtml {
-    EndpointHistoryOptions::default()
-        .write(&site, uri.path())
-        .await;
-    visit_tag_internal(Some(tag.as_str()), State(site)).await
+    Query(query): Query<PageQuery>,
+) -> axum::response::Response {
+    let feed_format = tag.rsplit_once('.').and_then(|(name, extension)| {
+        feed::FeedFormat::from_extension(extension).map(|format| (name, format))
+    });
+    if let Some((name, format)) = feed_format {
+        return match site.pages().blogs.tag_feeds.get(name) {
+            Some(feed) => feed.response(format, site.config().feed_content),
+            None => error::visit_404_internal(format!("/blog/tag/{tag}"), State(site), None)
+                .await
+                .into_response(),
+        };
+    }
+    let Some(page) = query.page() else {
+        let page = query.page.unwrap_or_default();
+        return error::visit_404_internal(
+            format!("/blog/tag/{tag}?page={page}"),
+            State(site),
+            None,
+        )
+        .await
+        .into_response();
+    };
+    visit_tag_internal(Some(tag.as_str()), State(site), page).await
 }
 
 /// Visit tag internal.
-pub async fn visit_tag_internal(tag: Option<&str>, State(site): State<Site>) -> RenderedHtml {
-    let page = format!("tags/{tag:?}");
-
-    // Collect metadata.
-    let mut metadata = site.pages().blogs.metadata.clone();
-    let mut blog_metadata: Vec<serde_json::Value> = Vec::new();
-    for blog in site.pages().blogs.blogs.iter() {
-        for other_tag in &blog.tags {
-            if Some(other_tag.as_str()) == tag {
-                let mut meta = blog.metadata.clone();
-                util::merge_json(
-                    &mut meta,
-                    &json!({"darken": blog_metadata.len().is_multiple_of(2), "path": blog.uri}),
-                )
-                .ok();
-                blog_metadata.push(meta);
+pub async fn visit_tag_internal(
+    tag: Option<&str>,
+    State(site): State<Site>,
+    page: usize,
+) -> axum::response::Response {
+    let cache_key = format!("tags/{tag:?}?page={page}");
+    let base = format!("/blog/tag/{}", tag.unwrap_or_default());
+    let posts = site
+        .pages()
+        .blogs
+        .blogs
+        .iter()
+        .flat_map(|blog| blog.tags.iter())
+        .filter(|other_tag| Some(other_tag.as_str()) == tag)
+        .count();
+    if !page_exists(posts, page, site.config().blog_page_size) {
+        return error::visit_404_internal(format!("{base}?page={page}"), State(site), None)
+            .await
+            .into_response();
+    }
+
+    site.clone()
+        .page_cache()
+        .retrieve_or_update(&cache_key, async move {
+            // Collect metadata.
+            let mut metadata = site.pages().blogs.metadata.clone();
+            let mut blog_metadata: Vec<serde_json::Value> = Vec::new();
+            for blog in site.pages().blogs.blogs.iter() {
+                for other_tag in &blog.tags {
+                    if Some(other_tag.as_str()) == tag {
+                        let mut meta = blog.metadata.clone();
+                        util::merge_json(
+                            &mut meta,
+                            &json!({"darken": blog_metadata.len().is_multiple_of(2), "path": blog.uri}),
+                        )
+                        .ok();
+                        blog_metadata.push(meta);
+                    }
+                }
             }
+            metadata["tag"] = match &tag {
+                Some(tag) => serde_json::Value::String((*tag).to_owned()),
+                None => serde_json::Value::String("".into()),
+            };
+            metadata["blogs"] = serde_json::Value::Array(blog_metadata);
+            paginate(&mut metadata, &base, page, site.config().blog_page_size);
+
+            site.render_page(&site.pages().blogs.index, &metadata)
+        })
+        .await
+        .into_response()
+}
+
+/// Endpoint for the archive of every post.
+pub async fn visit_archive(State(site): State<Site>) -> axum::response::Response {
+    visit_archive_internal(None, None, State(site)).await
+}
+
+/// Endpoint for the archive of a single month.
+pub async fn visit_archive_month(
+    Path((year, month)): Path<(String, String)>,
+    State(site): State<Site>,
+) -> axum::response::Response {
+    match (parse_year(&year), parse_month(&month)) {
+        (Some(year), Some(month)) => {
+            visit_archive_internal(Some(year), Some(month), State(site)).await
         }
+        _ => error::visit_404_internal(format!("/blog/{year}/{month}"), State(site), None)
+            .await
+            .into_response(),
     }
-    metadata["tag"] = match &tag {
-        Some(tag) => serde_json::Value::String((*tag).to_owned()),
-        None => serde_json::Value::String("".into()),
+}
+
+/// Visit archive internal. Periods without posts don't exist.
+async fn visit_archive_internal(
+    year: Option<i32>,
+    month: Option<u32>,
+    State(site): State<Site>,
+) -> axum::response::Response {
+    let path = match (year, month) {
+        (Some(year), Some(month)) => format!("/blog/{year}/{month:02}"),
+        (Some(year), None) => format!("/blog/{year}"),
+        _ => "/blog/archive".to_string(),
+    };
+    let Some(metadata) = site.pages().blogs.archive_metadata(year, month) else {
+        return error::visit_404_internal(path, State(site), None)
+            .await
+            .into_response();
     };
-    metadata["blogs"] = serde_json::Value::Array(blog_metadata);
 
     site.clone()
         .page_cache()
-        .retrieve_or_update(&page, async move {
-            site.render_page(&site.pages().blogs.index, &metadata)
+        .retrieve_or_update(&path[1..], async move {
+            site.render_page(&site.pages().blogs.archive, &metadata)
         })
         .await
+        .into_response()
+}
+
+/// Parse a four digit year.
+fn parse_year(year: &str) -> Option<i32> {
+    match year.len() == 4 && year.bytes().all(|b| b.is_ascii_digit()) {
+        true => year.parse().ok(),
+        false => None,
+    }
+}
+
+/// Parse a one or two digit month.
+fn parse_month(month: &str) -> Option<u32> {
+    match (1..=2).contains(&month.len()) && month.bytes().all(|b| b.is_ascii_digit()) {
+        true => month.parse().ok().filter(|month| (1..=12).contains(month)),
+        false => None,
+    }
 }
 
 /// Get local blog resource.
 pub async fn get_blog_resource(
-    uri: Uri,
     Path(resource): Path<String>,
     State(site): State<Site>,
 ) -> impl axum::response::IntoResponse {
     let blog_resource: String = format!("content/posts/media/{resource}");
 
-    let data = match site.packed_data().read_data(&blog_resource) {
-        Ok(data) => {
-            EndpointHistoryOptions::default()
-                .write(&site, uri.path())
-                .await;
-            data
-        }
+    let (status, data) = match site.packed_data().read_data(&blog_resource) {
+        Ok(data) => (StatusCode::OK, data),
         Err(_) => {
-            EndpointHistoryOptions::builder()
-                .valid(true)
-                .build()
-                .write(&site, uri.path())
-                .await;
             tracing::error!("Unable to render blog resource {blog_resource}");
-            EmbeddedData::empty()
+            (StatusCode::NOT_FOUND, EmbeddedData::empty())
         }
     };
-    crate::util::adjust_content_header(resource, data)
+    (status, crate::util::adjust_content_header(resource, data))
 }
 
 /// Get blog as atom feed.
-pub async fn visit_blog_feed(
-    uri: Uri,
-    State(site): State<Site>,
-) -> impl axum::response::IntoResponse {
-    EndpointHistoryOptions::default()
-        .write(&site, uri.path())
-        .await;
-    (
-        [(axum::http::header::CONTENT_TYPE, "application/atom+xml")],
-        site.pages().blogs.feed.clone(),
-    )
+pub async fn visit_blog_feed(State(site): State<Site>) -> axum::response::Response {
+    blog_feed(&site, feed::FeedFormat::Atom)
+}
+
+/// Get blog as RSS feed.
+pub async fn visit_blog_rss(State(site): State<Site>) -> axum::response::Response {
+    blog_feed(&site, feed::FeedFormat::Rss)
+}
+
+/// Get blog as JSON feed.
+pub async fn visit_blog_json(State(site): State<Site>) -> axum::response::Response {
+    blog_feed(&site, feed::FeedFormat::Json)
+}
+
+/// Blog feed response.
+fn blog_feed(site: &Site, format: feed::FeedFormat) -> axum::response::Response {
+    site.pages()
+        .blogs
+        .feed
+        .response(format, site.config().feed_content)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pages::blog::*;
+
+    #[test]
+    fn pagination() {
+        let listing = json!({ "blogs": [1, 2, 3, 4, 5] });
+
+        let mut metadata = listing.clone();
+        paginate(&mut metadata, "/blog", 1, 2);
+        assert_eq!(metadata["blogs"], json!([1, 2]));
+        assert_eq!(metadata["pagination"]["pages"], 3);
+        assert!(metadata["pagination"]["prev"].is_null());
+        assert_eq!(metadata["pagination"]["next"], "/blog?page=2");
+
+        let mut metadata = listing.clone();
+        paginate(&mut metadata, "/blog", 3, 2);
+        assert_eq!(metadata["blogs"], json!([5]));
+        assert_eq!(metadata["pagination"]["prev"], "/blog?page=2");
+        assert!(metadata["pagination"]["next"].is_null());
+
+        // Out of range pages don't exist.
+        assert!(!page_exists(5, 0, 2));
+        assert!(!page_exists(5, 4, 2));
+        assert!(page_exists(0, 1, 2));
+        assert!(!page_exists(0, 2, 2));
+        assert!(!page_exists(5, 2, 0));
+
+        // A single page needs no links.
+        let mut metadata = listing.clone();
+        paginate(&mut metadata, "/blog", 1, 10);
+        assert!(metadata.get("pagination").is_none());
+
+        // Anything other than a page number is treated as a missing page.
+        let query = |page: Option<&str>| PageQuery {
+            page: page.map(str::to_string),
+        };
+        assert_eq!(query(None).page(), Some(1));
+        assert_eq!(query(Some("2")).page(), Some(2));
+        assert_eq!(query(Some("abc")).page(), None);
+        assert_eq!(query(Some("")).page(), None);
+    }
+
+    #[test]
+    fn archive_periods() {
+        let post = |uri: &str, date: &str| Blog {
+            uri: uri.into(),
+            date: date.parse().unwrap(),
+            metadata: json!({}),
+            ..Default::default()
+        };
+        let pages = BlogsPages {
+            index: String::new(),
+            post: String::new(),
+            archive: String::new(),
+            series: String::new(),
+            drafts: Blogs::default(),
+            blogs: Blogs {
+                posts: vec![
+                    post("c", "2025-02-10"),
+                    post("b", "2024-11-02"),
+                    post("a", "2024-11-01"),
+                ],
+            },
+            feed: feed::Feed::default(),
+            tag_feeds: BTreeMap::new(),
+            metadata: json!({}),
+            series_metadata: BTreeMap::new(),
+            errors: Vec::new(),
+            next_scheduled: None,
+        };
+
+        let all = pages.archive_metadata(None, None).unwrap();
+        assert_eq!(all["count"], 3);
+        assert_eq!(all["years"][1]["year"], 2024);
+        assert_eq!(all["years"][1]["count"], 2);
+        assert_eq!(all["years"][1]["months"][0]["path"], "/blog/2024/11");
+        assert_eq!(all["years"][1]["months"][0]["blogs"][1]["path"], "a");
+
+        let month = pages.archive_metadata(Some(2024), Some(11)).unwrap();
+        assert_eq!(month["period"], "November 2024");
+        assert_eq!(month["count"], 2);
+        assert!(pages.archive_metadata(Some(2024), Some(12)).is_none());
+
+        assert_eq!(parse_year("2024"), Some(2024));
+        assert_eq!(parse_year("nix_1"), None);
+        assert_eq!(parse_month("04"), Some(4));
+        assert_eq!(parse_month("+4"), None);
+        assert_eq!(parse_month("13"), None);
+    }
+
+    #[test]
+    fn post_dates() {
+        let date: PostDate = "2026-03-01".parse().unwrap();
+        assert_eq!(date.time.to_rfc3339(), "2026-03-01T00:00:00+00:00");
+
+        let date: PostDate = "2026-03-01 09:30".parse().unwrap();
+        assert_eq!(date.time.to_rfc3339(), "2026-03-01T09:30:00+00:00");
+
+        // The calendar date is in the post's timezone.
+        let date: PostDate = "2026-03-01T07:00:00+09:00".parse().unwrap();
+        assert_eq!(date.time.to_utc().to_rfc3339(), "2026-02-28T22:00:00+00:00");
+        assert_eq!(date.to_string(), "2026-03-01T07:00:00+09:00");
+        assert_eq!(String::from(date), "2026-03-01");
+
+        assert!("2026-03-01T07:00+09:00".parse::<PostDate>().is_ok());
+        assert!("March 1st".parse::<PostDate>().is_err());
+    }
+
+    #[test]
+    fn updated_dates() {
+        let post = |uri: &str, date: &str, updated: &str| {
+            format!("+++\nname = \"{uri}\"\nblurb = \"\"\ndate = \"{date}\"\nupdated = \"{updated}\"\narticle = \"{uri}\"\n+++\n\nText.\n")
+        };
+        let packed_data = testing::packed_data(&[
+            (
+                "content/posts/2026-03-01-late.md",
+                &post("late", "2026-03-01T23:00:00-05:00", "2026-03-02"),
+            ),
+            (
+                "content/posts/2026-03-01-edited.md",
+                &post("edited", "2026-03-01T23:00:00-05:00", "2026-03-02 09:00"),
+            ),
+        ]);
+        let blogs = BlogsPages::new(Arc::new(packed_data)).unwrap();
+
+        // Midnight UTC on the 2nd is before the post went out, so it's dropped.
+        let late = blogs.get_blog("late").unwrap();
+        assert_eq!(late.updated, None);
+        assert_eq!(late.feed_item().updated, late.date.time);
+
+        let edited = blogs.get_blog("edited").unwrap();
+        let item = edited.feed_item();
+        assert_eq!(item.updated.to_rfc3339(), "2026-03-02T09:00:00+00:00");
+        assert!(item.updated > item.published);
+    }
+
+    #[test]
+    fn draft_tokens() {
+        let token = draft_token("secret", "drafty").unwrap();
+        assert_eq!(token.len(), 64);
+        assert_eq!(Some(&token), draft_token("secret", "drafty").as_ref());
+        assert_ne!(Some(&token), draft_token("secret", "other").as_ref());
+        assert_ne!(Some(&token), draft_token("other", "drafty").as_ref());
+        // An empty secret disables previews instead of being a known key.
+        assert_eq!(draft_token("", "drafty"), None);
+    }
+
+    #[test]
+    fn series_navigation() {
+        let post = |uri: &str, date: &str, series: Option<&str>, part: Option<u32>| Blog {
+            name: uri.to_uppercase(),
+            uri: uri.into(),
+            date: date.parse().unwrap(),
+            series: series.map(String::from),
+            series_part: part,
+            metadata: json!({}),
+            ..Default::default()
+        };
+        let mut blogs = Blogs {
+            posts: vec![
+                post("extra", "2025-03-01", Some("Web Scrapers"), None),
+                post("other", "2025-02-01", None, None),
+                post("first", "2025-01-01", Some("Web Scrapers"), Some(1)),
+                post("second", "2024-01-01", Some("web scrapers"), Some(2)),
+            ],
+        };
+        let series = link_series(&mut blogs).unwrap();
+
+        assert_eq!(series_slug("Rust & Nix"), "rust-nix");
+        assert_eq!(series.len(), 1);
+        let parts = &series["web-scrapers"];
+        assert_eq!(parts["parts"], 3);
+        assert_eq!(parts["blogs"][2]["path"], "extra");
+
+        // Explicit parts come first, regardless of date.
+        let second = &blogs[3].metadata["series_nav"];
+        assert_eq!(second["part"], 2);
+        assert_eq!(second["prev"]["path"], "first");
+        assert_eq!(second["next"]["path"], "extra");
+        assert!(blogs[2].metadata["series_nav"]["prev"].is_null());
+        assert!(blogs[1].metadata.get("series_nav").is_none());
+    }
+
+    #[test]
+    fn related_posts() {
+        let post = |uri: &str, date: &str, tags: &[&str], markdown: &str| Blog {
+            uri: uri.into(),
+            date: date.parse().unwrap(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            markdown: markdown.into(),
+            metadata: json!({}),
+            ..Default::default()
+        };
+        let mut blogs = Blogs {
+            posts: vec![
+                post(
+                    "newest",
+                    "2025-04-01",
+                    &["dev"],
+                    "feeds aggregators parsing",
+                ),
+                post("middle", "2025-03-01", &["life"], "travel"),
+                post(
+                    "oldest",
+                    "2025-02-01",
+                    &["dev"],
+                    "compilers parsing grammar",
+                ),
+                post(
+                    "feeds",
+                    "2025-01-01",
+                    &["dev"],
+                    "feeds aggregators syndication",
+                ),
+            ],
+        };
+        link_related(&mut blogs);
+
+        let newest = &blogs[0].metadata;
+        assert!(newest["post_nav"]["next"].is_null());
+        assert_eq!(newest["post_nav"]["prev"]["path"], "middle");
+        assert_eq!(blogs[3].metadata["post_nav"]["next"]["path"], "oldest");
+
+        // Shared tags are required, and similar wording ranks first.
+        let related: Vec<&serde_json::Value> = newest["related"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|post| &post["path"])
+            .collect();
+        assert_eq!(related, vec!["feeds", "oldest"]);
+        assert_eq!(blogs[1].metadata["related"], json!([]));
+    }
 }
@@ -2,6 +2,8 @@
 
 use super::*;
 
+use axum::http::StatusCode;
+
 pub const WORST_CASE_404: &str = "<html>404</html>";
 
 /// Error page.
@@ -14,21 +16,29 @@ pub struct ErrorPage {
 
 impl ErrorPage {
     /// Generate new error page.
-    pub fn new(_packed_data: Arc<PackedData>) -> Result<Self> {
+    pub fn new(packed_data: Arc<PackedData>) -> Result<Self> {
         Ok(ErrorPage {
-            raw_page: util::read_embedded_text::<EmbeddedPages>("404.html")?,
+            raw_page: read_page(&packed_data, "404.html")?,
             context: json!({}),
         })
     }
 }
 
+/// Bare error page for dev mode, showing what went wrong.
+pub fn dev_error_page(title: &str, error: &impl std::fmt::Display) -> RenderedHtml {
+    RenderedHtml::new(format!(
+        "<!doctype html><html><head><title>{title}</title></head><body>\
+         <h1>{title}</h1><pre style=\"white-space: pre-wrap\">{}</pre></body></html>",
+        handlebars::html_escape(&error.to_string()),
+    ))
+}
+
 /// Endpoint for error 404 page.
-pub async fn visit_404(uri: Uri, State(site): State<Site>, headers: HeaderMap) -> RenderedHtml {
-    EndpointHistoryOptions::builder()
-        .valid(true)
-        .build()
-        .write(&site, uri.path())
-        .await;
+pub async fn visit_404(
+    uri: Uri,
+    State(site): State<Site>,
+    headers: HeaderMap,
+) -> (StatusCode, RenderedHtml) {
     visit_404_internal(uri.path(), State(site), Some(headers)).await
 }
 
@@ -37,7 +47,7 @@ pub async fn visit_404_internal(
     path: impl AsRef<str>,
     State(site): State<Site>,
     headers: Option<HeaderMap>,
-) -> RenderedHtml {
+) -> (StatusCode, RenderedHtml) {
     // tracing::warn!("HEADERS: {:?}", headers);
     let remote_ip = match &headers {
         Some(headers) => match headers.get("x-forwarded-for") {
@@ -51,10 +61,12 @@ pub async fn visit_404_internal(
         path.as_ref(),
         remote_ip
     );
-    site.clone()
+    let page = site
+        .clone()
         .page_cache()
         .retrieve_or_update("404", async move {
             site.render_page(&site.pages().error.raw_page, &site.pages().error.context)
         })
-        .await
+        .await;
+    (StatusCode::NOT_FOUND, page)
 }
@@ -0,0 +1,338 @@
+//! Syndication feeds.
+//!
+//! Pages describe their entries once as feed items, which are rendered as
+//! Atom, RSS 2.0 and JSON Feed 1.1.
+
+use super::*;
+
+use atom_syndication as atom;
+use axum::response::IntoResponse;
+
+/// Feed author.
+const AUTHOR: &str = "Harrison Hall";
+
+/// Feed icon.
+const ICON: &str = "https://hachha.dev/media/catman.png";
+
+/// What feed entries carry.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+pub enum FeedContent {
+    /// Rendered post html, along with the blurb as summary.
+    #[default]
+    Full,
+    /// Only the blurb.
+    Summary,
+}
+
+/// Feed format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FeedFormat {
+    Atom,
+    Rss,
+    Json,
+}
+
+impl FeedFormat {
+    /// Every supported format.
+    const ALL: [FeedFormat; 3] = [FeedFormat::Atom, FeedFormat::Rss, FeedFormat::Json];
+
+    /// Format served at a path extension (e.g., `feed`, `rss`), if any.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "feed" | "xml" | "atom" => Some(FeedFormat::Atom),
+            "rss" => Some(FeedFormat::Rss),
+            "json" => Some(FeedFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// Response content type.
+    fn content_type(self) -> &'static str {
+        match self {
+            FeedFormat::Atom => "application/atom+xml",
+            FeedFormat::Rss => "application/rss+xml",
+            FeedFormat::Json => "application/feed+json",
+        }
+    }
+}
+
+/// Feed metadata.
+pub struct Channel {
+    /// Feed title.
+    pub title: String,
+    /// Feed description.
+    pub subtitle: String,
+    /// Page the feed belongs to.
+    pub link: String,
+    /// Absolute url of the JSON feed itself.
+    pub json_url: String,
+}
+
+/// Single feed entry.
+#[derive(Clone, Debug)]
+pub struct Item {
+    /// Entry title.
+    pub title: String,
+    /// Absolute url the entry links to, also used as its id.
+    pub link: String,
+    /// Short description.
+    pub summary: String,
+    /// Rendered html, for full content feeds.
+    pub content: Option<String>,
+    /// When the entry was published.
+    pub published: chrono::DateTime<chrono::FixedOffset>,
+    /// When the entry was last changed.
+    pub updated: chrono::DateTime<chrono::FixedOffset>,
+    /// Categories.
+    pub tags: Vec<String>,
+}
+
+/// Midnight UTC on `date`.
+pub fn midnight(date: chrono::NaiveDate) -> chrono::DateTime<chrono::FixedOffset> {
+    date.and_time(chrono::NaiveTime::default()).and_utc().into()
+}
+
+/// Feed rendered in every format, with and without entry content.
+#[derive(Default)]
+pub struct Feed {
+    rendered: BTreeMap<(FeedFormat, FeedContent), String>,
+}
+
+impl Feed {
+    /// Render a feed of `items`, most recently changed first.
+    pub fn new(channel: &Channel, items: &[Item]) -> Result<Self> {
+        let mut items = items.to_vec();
+        items.sort_by_key(|item| std::cmp::Reverse((item.updated, item.published)));
+        let items = items.as_slice();
+        let summaries: Vec<Item> = items
+            .iter()
+            .map(|item| Item {
+                content: None,
+                ..item.clone()
+            })
+            .collect();
+
+        let mut rendered = BTreeMap::new();
+        for format in FeedFormat::ALL {
+            for (content, items) in [
+                (FeedContent::Full, items),
+                (FeedContent::Summary, summaries.as_slice()),
+            ] {
+                let feed = match format {
+                    FeedFormat::Atom => render_atom(channel, items),
+                    FeedFormat::Rss => render_rss(channel, items),
+                    FeedFormat::Json => render_json(channel, items)?,
+                };
+                rendered.insert((format, content), feed);
+            }
+        }
+
+        Ok(Feed { rendered })
+    }
+
+    /// Rendered feed.
+    pub fn get(&self, format: FeedFormat, content: FeedContent) -> &str {
+        self.rendered
+            .get(&(format, content))
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    /// Feed response with the matching content type.
+    pub fn response(&self, format: FeedFormat, content: FeedContent) -> axum::response::Response {
+        (
+            [(axum::http::header::CONTENT_TYPE, format.content_type())],
+            self.get(format, content).to_string(),
+        )
+            .into_response()
+    }
+}
+
+/// Time of the most recent change to any item.
+fn last_updated(items: &[Item]) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    items.iter().map(|item| item.updated).max()
+}
+
+/// Render an Atom feed.
+fn render_atom(channel: &Channel, items: &[Item]) -> String {
+    let mut feed_builder = atom::FeedBuilder::default();
+    feed_builder
+        .title(channel.title.as_str())
+        .author(atom::PersonBuilder::default().name(AUTHOR).build())
+        .link(
+            atom::LinkBuilder::default()
+                .href(channel.link.as_str())
+                .title(channel.title.clone())
+                .build(),
+        )
+        .icon(ICON.to_string())
+        .subtitle(Some(channel.subtitle.clone().into()));
+    if let Some(updated) = last_updated(items) {
+        feed_builder.updated(updated);
+    }
+    let entries = items
+        .iter()
+        .map(|item| {
+            atom::EntryBuilder::default()
+                .title(item.title.clone())
+                .summary(Some(item.summary.clone().into()))
+                .content(item.content.as_ref().map(|html| {
+                    atom::ContentBuilder::default()
+                        .content_type(Some("html".to_string()))
+                        .value(Some(html.clone()))
+                        .build()
+                }))
+                .link(
+                    atom::LinkBuilder::default()
+                        .href(item.link.clone())
+                        .title(item.title.clone())
+                        .build(),
+                )
+                .published(item.published)
+                .updated(item.updated)
+                .categories(
+                    item.tags
+                        .iter()
+                        .map(|tag| atom::CategoryBuilder::default().term(tag).build())
+                        .collect::<Vec<atom::Category>>(),
+                )
+                .build()
+        })
+        .collect::<Vec<atom::Entry>>();
+    feed_builder.entries(entries);
+
+    feed_builder.build().to_string()
+}
+
+/// Render an RSS 2.0 feed.
+fn render_rss(channel: &Channel, items: &[Item]) -> String {
+    let last_build_date = last_updated(items).map(|updated| updated.to_rfc2822());
+    let items = items
+        .iter()
+        .map(|item| {
+            rss::ItemBuilder::default()
+                .title(Some(item.title.clone()))
+                .link(Some(item.link.clone()))
+                .guid(Some(
+                    rss::GuidBuilder::default()
+                        .value(item.link.clone())
+                        .permalink(true)
+                        .build(),
+                ))
+                .description(Some(item.summary.clone()))
+                .content(item.content.clone())
+                .pub_date(Some(item.published.to_rfc2822()))
+                .categories(
+                    item.tags
+                        .iter()
+                        .map(|tag| rss::CategoryBuilder::default().name(tag.clone()).build())
+                        .collect::<Vec<rss::Category>>(),
+                )
+                .build()
+        })
+        .collect::<Vec<rss::Item>>();
+
+    rss::ChannelBuilder::default()
+        .namespaces(BTreeMap::from([(
+            "content".to_string(),
+            "http://purl.org/rss/1.0/modules/content/".to_string(),
+        )]))
+        .title(channel.title.clone())
+        .link(channel.link.clone())
+        .description(channel.subtitle.clone())
+        .last_build_date(last_build_date)
+        .image(Some(
+            rss::ImageBuilder::default()
+                .url(ICON.to_string())
+                .title(channel.title.clone())
+                .link(channel.link.clone())
+                .build(),
+        ))
+        .items(items)
+        .build()
+        .to_string()
+}
+
+/// Render a JSON Feed 1.1 feed.
+fn render_json(channel: &Channel, items: &[Item]) -> Result<String> {
+    let items: Vec<serde_json::Value> = items
+        .iter()
+        .map(|item| {
+            let mut entry = json!({
+                "id": item.link,
+                "url": item.link,
+                "title": item.title,
+                "summary": item.summary,
+                "date_published": item.published.to_rfc3339(),
+                "date_modified": item.updated.to_rfc3339(),
+            });
+            // Items must carry content, so summaries double as plain text.
+            match &item.content {
+                Some(html) => entry["content_html"] = json!(html),
+                None => entry["content_text"] = json!(item.summary),
+            }
+            if !item.tags.is_empty() {
+                entry["tags"] = json!(item.tags);
+            }
+            entry
+        })
+        .collect();
+
+    Ok(serde_json::to_string_pretty(&json!({
+        "version": "https://jsonfeed.org/version/1.1",
+        "title": channel.title,
+        "home_page_url": channel.link,
+        "feed_url": channel.json_url,
+        "description": channel.subtitle,
+        "icon": ICON,
+        "authors": [{ "name": AUTHOR }],
+        "items": items,
+    }))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pages::feed::*;
+
+    #[test]
+    fn feed_formats() {
+        let channel = Channel {
+            title: "Test".into(),
+            subtitle: "Test feed".into(),
+            link: "https://hachha.dev".into(),
+            json_url: "https://hachha.dev/test.json".into(),
+        };
+        let items = [Item {
+            title: "Post".into(),
+            link: "https://hachha.dev/blog/post".into(),
+            summary: "A post".into(),
+            content: Some("<p>Hello</p>".into()),
+            published: midnight("2025-02-10".parse().unwrap()),
+            updated: midnight("2025-03-01".parse().unwrap()),
+            tags: vec!["dev".into()],
+        }];
+        let feed = Feed::new(&channel, &items).unwrap();
+
+        let atom = feed.get(FeedFormat::Atom, FeedContent::Full);
+        assert!(atom.contains(r#"<content type="html">"#));
+        assert!(atom.contains("<updated>2025-03-01T00:00:00+00:00</updated>"));
+        assert!(!feed
+            .get(FeedFormat::Atom, FeedContent::Summary)
+            .contains("<content"));
+
+        let rss = feed.get(FeedFormat::Rss, FeedContent::Full);
+        assert!(rss.contains("<content:encoded><![CDATA[<p>Hello</p>]]></content:encoded>"));
+        assert!(rss.contains("<pubDate>Mon, 10 Feb 2025 00:00:00 +0000</pubDate>"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(feed.get(FeedFormat::Json, FeedContent::Summary)).unwrap();
+        assert_eq!(json["feed_url"], "https://hachha.dev/test.json");
+        assert_eq!(json["items"][0]["id"], "https://hachha.dev/blog/post");
+        assert_eq!(json["items"][0]["content_text"], "A post");
+        assert_eq!(
+            json["items"][0]["date_modified"],
+            "2025-03-01T00:00:00+00:00"
+        );
+        assert!(json["items"][0].get("content_html").is_none());
+    }
+}
@@ -14,7 +14,7 @@ impl IndexPage {
     /// Generate new index page.
     pub fn new(packed_data: Arc<PackedData>) -> Result<Self> {
         Ok(IndexPage {
-            raw_page: read_embedded_text::<EmbeddedPages>("index.html")?,
+            raw_page: read_page(&packed_data, "index.html")?,
             config: packed_data.read_toml::<Config>("content/index_phrases.toml")?,
         })
     }
@@ -50,10 +50,7 @@ struct Link {
 }
 
 /// Endpoint for site index.
-pub async fn visit_index(uri: Uri, State(site): State<Site>) -> RenderedHtml {
-    EndpointHistoryOptions::default()
-        .write(&site, uri.path())
-        .await;
+pub async fn visit_index(State(site): State<Site>) -> RenderedHtml {
     site.clone()
         .page_cache()
         .retrieve_or_update("index", async move {
@@ -2,14 +2,12 @@
 
 use super::*;
 
-use atom_syndication as atom;
-
 /// The links page and subpages.
 pub struct LinksPage {
     index: String,
     #[allow(unused)]
     links: Links,
-    feed: String,
+    feed: feed::Feed,
     metadata: serde_json::Value,
 }
 
@@ -17,49 +15,33 @@ impl LinksPage {
     /// Generate new links pages.
     pub fn new(packed_data: Arc<PackedData>) -> Result<Self> {
         // Parse pages.
-        let index = util::read_embedded_text::<EmbeddedPages>("links.html")?;
+        let index = read_page(&packed_data, "links.html")?;
         let mut links = packed_data.read_toml::<Links>("content/links.toml")?;
         links.sort();
         links.reverse();
 
-        // Parse into atom feed.
-        let mut feed_builder = atom::FeedBuilder::default();
-        feed_builder
-            .title("hachha.dev Links")
-            .author(atom::PersonBuilder::default().name("Harrison Hall").build())
-            .link(
-                atom::LinkBuilder::default()
-                    .href("https://hachha.dev")
-                    .title("hachha.dev".to_string())
-                    .build(),
-            )
-            .icon("https://hachha.dev/media/catman.png".to_string())
-            .subtitle(Some("hachha.dev link feed".to_string().into()));
-        let mut entries = Vec::new();
-        for link in links.iter() {
-            let timestamp: chrono::DateTime<chrono::FixedOffset> = link
-                .date
-                .and_time(chrono::NaiveTime::default())
-                .and_local_timezone(chrono::Utc)
-                .latest()
-                .ok_or(anyhow!("Unable to convert dt {} to utc.", link.date))?
-                .into();
-            let entry: atom::Entry = atom::EntryBuilder::default()
-                .title(link.title.clone())
-                .summary(Some(link.description.clone().into()))
-                .link(
-                    atom::LinkBuilder::default()
-                        .href(link.url.clone())
-                        .title(link.title.clone())
-                        .build(),
-                )
-                .published(timestamp)
-                .updated(timestamp)
-                .build();
-            entries.push(entry);
-        }
-        feed_builder.entries(entries);
-        let feed = feed_builder.build().to_string();
+        // Parse into feeds.
+        let items: Vec<feed::Item> = links
+            .iter()
+            .map(|link| feed::Item {
+                title: link.title.clone(),
+                link: link.url.clone(),
+                summary: link.description.clone(),
+                content: None,
+                published: feed::midnight(link.date),
+                updated: feed::midnight(link.date),
+                tags: Vec::new(),
+            })
+            .collect();
+        let feed = feed::Feed::new(
+            &feed::Channel {
+                title: "hachha.dev Links".into(),
+                subtitle: "hachha.dev link feed".into(),
+                link: "https://hachha.dev".into(),
+                json_url: "https://hachha.dev/links/feed.json".into(),
+            },
+            &items,
+        )?;
 
         // Generate metadata. Darken every other entry.
         let mut metadata = json!({});
@@ -138,10 +120,7 @@ impl std::cmp::Ord for Link {
 }
 
 /// Endpoint for links index page.
-pub async fn visit_links_index(uri: Uri, State(site): State<Site>) -> RenderedHtml {
-    EndpointHistoryOptions::default()
-        .write(&site, uri.path())
-        .await;
+pub async fn visit_links_index(State(site): State<Site>) -> RenderedHtml {
     site.clone()
         .page_cache()
         .retrieve_or_update("links", async move {
@@ -151,15 +130,24 @@ pub async fn visit_links_index(uri: Uri, State(site): State<Site>) -> RenderedHt
 }
 
 /// Get links as atom feed.
-pub async fn visit_links_feed(
-    uri: Uri,
-    State(site): State<Site>,
-) -> impl axum::response::IntoResponse {
-    EndpointHistoryOptions::default()
-        .write(&site, uri.path())
-        .await;
-    (
-        [(axum::http::header::CONTENT_TYPE, "application/atom+xml")],
-        site.pages().links.feed.clone(),
-    )
+pub async fn visit_links_feed(State(site): State<Site>) -> axum::response::Response {
+    links_feed(&site, feed::FeedFormat::Atom)
+}
+
+/// Get links as RSS feed.
+pub async fn visit_links_rss(State(site): State<Site>) -> axum::response::Response {
+    links_feed(&site, feed::FeedFormat::Rss)
+}
+
+/// Get links as JSON feed.
+pub async fn visit_links_json(State(site): State<Site>) -> axum::response::Response {
+    links_feed(&site, feed::FeedFormat::Json)
+}
+
+/// Links feed response. Links carry no content of their own.
+fn links_feed(site: &Site, format: feed::FeedFormat) -> axum::response::Response {
+    site.pages()
+        .links
+        .feed
+        .response(format, feed::FeedContent::Summary)
 }
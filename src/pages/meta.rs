@@ -14,9 +14,9 @@ impl MetaPages {
 
 #[derive(Serialize, Deserialize)]
 pub struct VersionData {
-    version: String,
-    commit_hash: String,
-    build_time: String,
+    pub version: String,
+    pub commit_hash: String,
+    pub build_time: String,
 }
 
 impl Default for VersionData {
@@ -43,9 +43,6 @@ impl Default for VersionData {
 }
 
 /// Endpoint for site version.
-pub async fn version(uri: Uri, State(site): State<Site>) -> axum::Json<VersionData> {
-    EndpointHistoryOptions::default()
-        .write(&site, uri.path())
-        .await;
+pub async fn version() -> axum::Json<VersionData> {
     VersionData::default().into()
 }
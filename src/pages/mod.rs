@@ -2,8 +2,10 @@
 
 use super::*;
 
+pub mod admin;
 pub mod blog;
 pub mod error;
+pub mod feed;
 pub mod index;
 pub mod links;
 pub mod meta;
@@ -12,6 +14,7 @@ pub mod slashpages;
 
 /// All pages helper.
 pub struct Pages {
+    pub admin: admin::AdminPages,
     pub index: index::IndexPage,
     pub error: error::ErrorPage,
     pub blogs: blog::BlogsPages,
@@ -26,6 +29,7 @@ impl Pages {
     /// Generate helper for all pages.
     pub fn new(packed_data: Arc<PackedData>) -> Result<Self> {
         Ok(Pages {
+            admin: admin::AdminPages::new(packed_data.clone())?,
             index: index::IndexPage::new(packed_data.clone())?,
             error: error::ErrorPage::new(packed_data.clone())?,
             blogs: blog::BlogsPages::new(packed_data.clone())?,
@@ -35,6 +39,38 @@ impl Pages {
             meta: meta::MetaPages::new(packed_data.clone())?,
         })
     }
+
+    /// Closest existing page to a missing path, if any is close enough to
+    /// redirect to. Only the last path segment is compared, since that's where
+    /// typos and renames usually are.
+    pub fn suggest_redirect(&self, missing: &str) -> Option<String> {
+        let slug = |path: &str| {
+            path.trim_end_matches('/')
+                .rsplit('/')
+                .next()
+                .unwrap_or_default()
+                .trim_end_matches(".html")
+                .to_lowercase()
+        };
+        let missing_slug = slug(missing);
+        if missing_slug.is_empty() {
+            return None;
+        }
+
+        let mut targets = self.blogs.paths();
+        targets.extend(self.slashpages.paths());
+        targets
+            .into_iter()
+            .map(|target| (util::edit_distance(&missing_slug, &slug(&target)), target))
+            .filter(|(distance, _)| *distance <= (missing_slug.chars().count() / 3).max(2))
+            .min()
+            .map(|(_, target)| target)
+    }
+
+    /// Content errors that were skipped while building pages.
+    pub fn errors(&self) -> Vec<String> {
+        self.blogs.errors.clone()
+    }
 }
 
 /// Embedded page templates.
@@ -42,3 +78,84 @@ impl Pages {
 #[folder = "resources/pages"]
 #[include = "*.html"]
 struct EmbeddedPages;
+
+/// Read a page template, from the content directory in dev mode so edits show
+/// up without rebuilding.
+fn read_page(packed_data: &PackedData, name: &str) -> Result<String> {
+    match packed_data.is_live() {
+        true => packed_data.read_text(format!("resources/pages/{name}")),
+        false => util::read_embedded_text::<EmbeddedPages>(name),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+
+    /// Smallest content the site builds from, plus `files`.
+    pub fn packed_data(files: &[(&str, &str)]) -> PackedData {
+        let mut all = vec![
+            ("content/index_phrases.toml", "phrases = []\nlinks = []\n"),
+            ("content/links.toml", "links = []\n"),
+            ("content/projects.toml", "projects = []\n"),
+            (
+                "resources/styles/theme/rules.toml",
+                "default = \"plain.css\"\nrules = []\n",
+            ),
+            ("resources/styles/theme/plain.css", ""),
+        ];
+        all.extend_from_slice(files);
+        PackedData::from_files(&all, false)
+    }
+
+    /// Markdown of a post published on `date`.
+    pub fn post(uri: &str, date: &str) -> String {
+        format!("+++\nname = \"{uri}\"\nblurb = \"\"\ndate = \"{date}\"\narticle = \"{uri}\"\n+++\n\nText.\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pages::*;
+
+    #[test]
+    fn live_page_templates() {
+        let files = [("resources/pages/blogs.html", "edited")];
+        let live = PackedData::from_files(&files, true);
+        assert_eq!(read_page(&live, "blogs.html").unwrap(), "edited");
+        assert!(read_page(&live, "post.html").is_err());
+
+        // Bundled content uses the templates built into the binary.
+        let bundled = PackedData::from_files(&files, false);
+        assert_ne!(read_page(&bundled, "blogs.html").unwrap(), "edited");
+    }
+
+    #[test]
+    fn redirect_suggestions() {
+        let packed_data = testing::packed_data(&[
+            (
+                "content/posts/2025-02-15-scrapers.md",
+                &testing::post("scrapers", "2025-02-15"),
+            ),
+            (
+                "content/posts/2025-01-29-slipknot.md",
+                &testing::post("slipknot", "2025-01-29"),
+            ),
+            ("content/slashpages/colophon.md", "How this site is made."),
+        ]);
+        let pages = Pages::new(Arc::new(packed_data)).unwrap();
+        let suggest = |missing: &str| pages.suggest_redirect(missing);
+        assert_eq!(suggest("/blog/scraper").as_deref(), Some("/blog/scrapers"));
+        assert_eq!(suggest("/blog/Colophn").as_deref(), Some("/colophon"));
+        assert_eq!(suggest("/colophon.html").as_deref(), Some("/colophon"));
+
+        // Up to a third of the slug may differ.
+        assert_eq!(suggest("/colophxxx").as_deref(), Some("/colophon"));
+        assert_eq!(suggest("/colophxxxx"), None);
+
+        // Nothing close enough.
+        assert_eq!(suggest("/blog/zzzzzzzzzz"), None);
+        assert_eq!(suggest("/wp-login.php"), None);
+        assert_eq!(suggest("/"), None);
+    }
+}
@@ -20,7 +20,7 @@ impl ProjectsPage {
     /// Generate new projects page.
     pub fn new(packed_data: Arc<PackedData>) -> Result<Self> {
         // Parse index file.
-        let raw_index = util::read_embedded_text::<EmbeddedPages>("projects.html")?;
+        let raw_index = read_page(&packed_data, "projects.html")?;
 
         // Parse projects.
         let mut projects = packed_data.read_toml::<Projects>("content/projects.toml")?;
@@ -124,10 +124,7 @@ impl std::cmp::Ord for Project {
 }
 
 /// Endpoint for project index page.
-pub async fn visit_projects(uri: Uri, State(site): State<Site>) -> RenderedHtml {
-    EndpointHistoryOptions::default()
-        .write(&site, uri.path())
-        .await;
+pub async fn visit_projects(State(site): State<Site>) -> RenderedHtml {
     site.clone()
         .page_cache()
         .retrieve_or_update("projects", async move {
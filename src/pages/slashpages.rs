@@ -2,6 +2,8 @@
 
 use super::*;
 
+use axum::response::IntoResponse;
+
 /// The index (home) page.
 pub struct SlashPages {
     /// Unrendered page.
@@ -14,7 +16,7 @@ impl SlashPages {
     /// Generate new index page.
     pub fn new(packed_data: Arc<PackedData>) -> Result<Self> {
         let mut sp = Self {
-            raw_page: read_embedded_text::<EmbeddedPages>("slashpage.html")?,
+            raw_page: read_page(&packed_data, "slashpage.html")?,
             pages: HashMap::new(),
         };
 
@@ -47,6 +49,11 @@ impl SlashPages {
         Ok(sp)
     }
 
+    /// Paths of every slashpage.
+    pub fn paths(&self) -> Vec<String> {
+        self.pages.keys().map(|page| format!("/{page}")).collect()
+    }
+
     /// Add slashpages.
     pub fn add_routes(&self, mut router: Router<Site>) -> Router<Site> {
         for slashpage in self.pages.keys() {
@@ -63,7 +70,7 @@ pub async fn visit_slashpage(
     uri: Uri,
     State(site): State<Site>,
     headers: HeaderMap,
-) -> RenderedHtml {
+) -> axum::response::Response {
     let slashpage = match uri.path().strip_prefix("/") {
         Some(t) => t,
         None => uri.path(),
@@ -71,7 +78,9 @@ pub async fn visit_slashpage(
     .to_owned();
     if !site.pages().slashpages.pages.contains_key(&slashpage) {
         tracing::error!("Invalid slashpage: {}", slashpage);
-        return error::visit_404_internal(slashpage, State(site), Some(headers)).await;
+        return error::visit_404_internal(slashpage, State(site), Some(headers))
+            .await
+            .into_response();
     }
 
     let page_name = slashpage.clone();
@@ -89,4 +98,5 @@ pub async fn visit_slashpage(
             site.render_page(&site.pages().slashpages.raw_page, &context)
         })
         .await
+        .into_response()
 }
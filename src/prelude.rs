@@ -1,5 +1,6 @@
-pub use crate::cache::Cache;
-pub use crate::db::{Database, EndpointHistoryOptions};
+pub use crate::analytics::{AgentClassifier, JunkFilter};
+pub use crate::cache::{Cache, CacheWeight};
+pub use crate::db::{Database, HistoryWriter};
 pub use crate::pages::Pages;
 pub use crate::site::Site;
 pub use crate::theme::ThemeProvider;
@@ -12,7 +13,7 @@ pub(crate) mod internal {
     pub use std::net::SocketAddr;
     pub use std::path::PathBuf;
     pub use std::sync::Arc;
-    pub use std::time::Instant;
+    pub use std::time::{Duration, Instant, SystemTime};
 
     pub use axum::extract::{Path, State};
     pub use axum::http::{HeaderMap, Uri};
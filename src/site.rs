@@ -12,8 +12,16 @@ pub struct Site(Arc<SiteWrapped>);
 
 impl Site {
     /// Generate shared site.
-    pub async fn new() -> Result<Self> {
-        Ok(Site(Arc::new(SiteWrapped::new().await?)))
+    pub async fn new(config: SiteConfig) -> Result<Self> {
+        // Parse packed data into memory.
+        let packed_data = config.load_packed_data()?;
+        Ok(Site(Arc::new(SiteWrapped::new(config, packed_data).await?)))
+    }
+
+    /// Create a site serving the given packed data, for tests.
+    #[cfg(test)]
+    pub async fn with_packed_data(config: SiteConfig, packed_data: PackedData) -> Result<Self> {
+        Ok(Site(Arc::new(SiteWrapped::new(config, packed_data).await?)))
     }
 
     /// Serve site.
@@ -27,12 +35,21 @@ impl Site {
         app = app.route("/media/{*path}", get(resources::get_media));
         app = app.route("/blog", get(pages::blog::visit_blog_index));
         app = app.route("/blog.feed", get(pages::blog::visit_blog_feed));
-        app = app.route("/blog/{:path}", get(pages::blog::visit_blog));
+        app = app.route("/blog.rss", get(pages::blog::visit_blog_rss));
+        app = app.route("/blog.json", get(pages::blog::visit_blog_json));
+        app = app.route("/blog/{path}", get(pages::blog::visit_blog));
         app = app.route(
             "/blog/media/{*resource}",
             get(pages::blog::get_blog_resource),
         );
-        app = app.route("/blog/tag/{:tag}", get(pages::blog::visit_tag));
+        app = app.route("/blog/tag/{tag}", get(pages::blog::visit_tag));
+        app = app.route("/blog/archive", get(pages::blog::visit_archive));
+        app = app.route("/blog/draft/{uri}", get(pages::blog::visit_draft));
+        app = app.route("/blog/series/{series}", get(pages::blog::visit_series));
+        app = app.route(
+            "/blog/{year}/{month}",
+            get(pages::blog::visit_archive_month),
+        );
         app = Site::add_redirect(app, "/feed", "/blog.feed");
         app = Site::add_redirect(app, "/feed.xml", "/blog.feed");
         app = Site::add_redirect(app, "/atom.xml", "/blog.feed");
@@ -41,27 +58,82 @@ impl Site {
         app = Site::add_redirect(app, "/postroll", "/links");
         app = Site::add_redirect(app, "/parks", "https://parks.hachha.dev");
         app = app.route("/links/feed.xml", get(pages::links::visit_links_feed));
+        app = app.route("/links/feed.rss", get(pages::links::visit_links_rss));
+        app = app.route("/links/feed.json", get(pages::links::visit_links_json));
+        app = Site::add_redirect(app, "/links.rss", "/links/feed.rss");
+        app = Site::add_redirect(app, "/links.json", "/links/feed.json");
         app = Site::add_redirect(app, "/links.feed", "/links/feed.xml");
         app = Site::add_redirect(app, "/links/atom.xml", "/links/feed.xml");
         app = app.route("/projects", get(pages::projects::visit_projects));
         app = app.route("/favicon.ico", get(resources::get_favicon));
         app = app.route("/robots.txt", get(resources::get_robots_txt));
         app = app.route("/version", get(pages::meta::version));
+        app = app.route(
+            "/admin/cache",
+            get(pages::admin::list_cache).delete(pages::admin::purge_cache),
+        );
+        app = app.route("/admin/stats", get(pages::admin::visit_stats));
+        app = app.route(
+            "/admin/login",
+            axum::routing::post(pages::admin::admin_login),
+        );
+        app = app.route("/admin/broken-links", get(pages::admin::list_broken_links));
+        app = app.route("/admin/drafts", get(pages::admin::list_drafts));
+        app = app.route(
+            "/admin/backup",
+            axum::routing::post(pages::admin::create_backup),
+        );
+        app = app.route(
+            "/admin/subscribers",
+            get(pages::admin::list_feed_subscribers),
+        );
         app = app.fallback(get(pages::error::visit_404));
         app = app.layer(tower_http::trace::TraceLayer::new_for_http());
 
         // Add slash pages.
         app = self.pages().slashpages.add_routes(app);
 
+        // Record every request.
+        app = app.layer(axum::middleware::from_fn_with_state(
+            self.clone(),
+            db::record_endpoint_history,
+        ));
+
         // Add self as state.
         let app = app.with_state(self.clone());
 
+        // Keep the footer subscriber estimate current.
+        let site = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(site.config().maintenance_interval());
+            loop {
+                interval.tick().await;
+                site.refresh_subscriber_estimate().await;
+            }
+        });
+
+        // Publish scheduled posts when they're due.
+        pages::blog::spawn_scheduled_publishing(self.clone());
+
+        // Watch content in dev mode.
+        if self.config().dev {
+            dev::watch_content(self.clone());
+        }
+
         // Serve.
         tracing::info!("Serving haccha.dev on {}", self.config().port);
         tracing::debug!("Debug @ http://127.0.0.1:{}", self.config().port);
         let addr = SocketAddr::from(([0, 0, 0, 0], self.config().port));
         let listener = tokio::net::TcpListener::bind(addr).await?;
-        axum::serve(listener, app.into_make_service()).await?;
+        axum::serve(listener, app.into_make_service())
+            .with_graceful_shutdown(shutdown_signal())
+            .await?;
+
+        // Flush pending writes.
+        tracing::info!("Shutting down.");
+        if let Some(history) = self.history() {
+            history.shutdown().await;
+        }
 
         Ok(())
     }
@@ -92,28 +164,90 @@ impl Site {
         &self.0.db
     }
 
+    /// Get endpoint history writer, unless analytics are disabled.
+    pub fn history(&self) -> Option<&HistoryWriter> {
+        self.0.history.as_ref()
+    }
+
+    /// Get junk path filter.
+    pub fn junk_filter(&self) -> &JunkFilter {
+        &self.0.junk_filter
+    }
+
+    /// Get user agent classifier.
+    pub fn agent_classifier(&self) -> &AgentClassifier {
+        &self.0.agent_classifier
+    }
+
     /// Get pages.
     pub fn pages(&self) -> Arc<Pages> {
-        match cfg!(debug_assertions) {
-            true => match pages::Pages::new(self.packed_data()) {
-                Ok(pages) => Arc::new(pages),
-                Err(e) => {
-                    tracing::error!("Unable to rebuild pages!");
-                    panic!("{e}");
-                }
-            },
-            false => self.0.pages.clone(),
-        }
+        self.0.pages.get()
     }
 
     /// Get theme provider.
     pub fn theme_provider(&self) -> Arc<ThemeProvider> {
-        self.0.theme_provider.clone()
+        self.0.theme_provider.get()
     }
 
     /// Get packed data.
     pub fn packed_data(&self) -> Arc<PackedData> {
-        self.0.packed_data.clone()
+        self.0.packed_data.get()
+    }
+
+    /// Rebuild pages and theme from new packed data and clear the page cache.
+    /// On failure the previous pages are kept, and in dev mode the error is
+    /// shown in the browser until the next successful rebuild.
+    pub async fn reload(&self, packed_data: PackedData) {
+        let packed_data = Arc::new(packed_data);
+        let rebuilt = Pages::new(packed_data.clone()).and_then(|pages| {
+            Ok((
+                pages,
+                ThemeProvider::new(packed_data.clone())?,
+                create_templater(&packed_data)?,
+            ))
+        });
+        match rebuilt {
+            Ok((pages, theme_provider, templater)) => {
+                let errors = pages.errors();
+                for error in errors.iter() {
+                    tracing::error!("{error}");
+                }
+                if self.config().dev {
+                    self.0.build_error.set(match errors.is_empty() {
+                        true => None,
+                        false => Some(errors.join("\n")),
+                    });
+                }
+                self.0.packed_data.set(packed_data);
+                self.0.pages.set(pages);
+                self.0.theme_provider.set(theme_provider);
+                self.0.templater.set(templater);
+                tracing::info!("Rebuilt site content.");
+            }
+            Err(e) => {
+                tracing::error!("Unable to rebuild pages: {e}");
+                if self.config().dev {
+                    self.0.build_error.set(Some(format!("{e:?}")));
+                }
+            }
+        }
+        self.page_cache().purge_all().await;
+    }
+
+    /// Recompute the blog feed subscriber estimate shown in the footer.
+    pub async fn refresh_subscriber_estimate(&self) {
+        match self
+            .db()
+            .blog_feed_subscribers(SUBSCRIBER_MAX_AGE_DAYS)
+            .await
+        {
+            Ok(total) => {
+                self.0
+                    .subscriber_estimate
+                    .store(total.max(0) as u64, std::sync::atomic::Ordering::Relaxed);
+            }
+            Err(e) => tracing::warn!("Unable to estimate feed subscribers: {e}"),
+        }
     }
 
     /// Get page cache.
@@ -127,11 +261,19 @@ impl Site {
         json!({
             "version": env!("CARGO_PKG_VERSION"),
             "year": current_time.year(),
+            "subscribers": self.0.subscriber_estimate.load(std::sync::atomic::Ordering::Relaxed),
         })
     }
 
     /// Render page with templater given json values.
     pub fn render_page(&self, page: impl AsRef<str>, metadata: &serde_json::Value) -> RenderedHtml {
+        // Surface build errors in dev mode.
+        if self.config().dev {
+            if let Some(error) = &*self.0.build_error.get() {
+                return pages::error::dev_error_page("Unable to build site", error);
+            }
+        }
+
         // Compute complete json to render page.
         let mut render_context = self.base_context();
         if util::merge_json(&mut render_context, metadata).is_err() {
@@ -143,11 +285,15 @@ impl Site {
             match self
                 .0
                 .templater
+                .get()
                 .render_template(page.as_ref(), &render_context)
             {
                 Ok(rendered_page) => rendered_page,
                 Err(e) => {
                     tracing::error!("Error rendering page: {e}");
+                    if self.config().dev {
+                        return pages::error::dev_error_page("Unable to render page", &e);
+                    }
                     pages::error::WORST_CASE_404.to_string()
                 }
             },
@@ -159,34 +305,82 @@ impl Site {
 struct SiteWrapped {
     config: SiteConfig,
     db: Arc<Database>,
-    templater: Arc<Handlebars<'static>>,
-    pages: Arc<Pages>,
-    theme_provider: Arc<ThemeProvider>,
-    packed_data: Arc<PackedData>,
+    history: Option<HistoryWriter>,
+    agent_classifier: AgentClassifier,
+    junk_filter: JunkFilter,
+    templater: Reloadable<Handlebars<'static>>,
+    pages: Reloadable<Pages>,
+    theme_provider: Reloadable<ThemeProvider>,
+    packed_data: Reloadable<PackedData>,
+    /// Latest content build error, only tracked in dev mode.
+    build_error: Reloadable<Option<String>>,
     page_cache: Cache<RenderedHtml>,
+    /// Estimated number of blog feed subscribers across aggregators.
+    subscriber_estimate: std::sync::atomic::AtomicU64,
 }
 
+/// Days after which an aggregator that stopped polling leaves the subscriber
+/// estimate.
+pub const SUBSCRIBER_MAX_AGE_DAYS: u32 = 14;
+
 impl SiteWrapped {
     /// Generate new site object.
-    async fn new() -> Result<Self> {
-        // Parse arguments.
-        let args: SiteConfig = SiteConfig::parse();
-
-        // Set logging.
-        color_eyre::install()?;
-        util::init_logging(args.debug)?;
+    async fn new(args: SiteConfig, packed_data: PackedData) -> Result<Self> {
+        let packed_data = Arc::new(packed_data);
+        let pages = Pages::new(packed_data.clone())?;
+        let build_error = match (args.dev, pages.errors()) {
+            (true, errors) if !errors.is_empty() => Some(errors.join("\n")),
+            _ => None,
+        };
 
-        // Parse packed data into memory.
-        let packed_data = Arc::new(PackedData::new(args.packed_data())?);
+        let db = Arc::new(Database::new(args.database()).await?);
+        match args.no_analytics {
+            true => tracing::info!("Analytics disabled: not recording any history."),
+            false => {
+                let version = pages::meta::VersionData::default();
+                db.record_deploy(&db::DeployRecord {
+                    version: version.version,
+                    timestamp: chrono::Utc::now().timestamp(),
+                    commit_hash: Some(version.commit_hash),
+                    build_time: Some(version.build_time),
+                    data_hash: Some(packed_data.content_hash()),
+                })
+                .await?;
+                db.clone()
+                    .spawn_maintenance(args.retention(), args.maintenance_interval());
+            }
+        }
+        if args.backup_interval_hours > 0 {
+            db.clone().spawn_backups(
+                args.backup(),
+                Duration::from_secs(args.backup_interval_hours * 60 * 60),
+            );
+        }
+        let history = (!args.no_analytics).then(|| {
+            HistoryWriter::spawn(
+                db.clone(),
+                args.history_queue_size,
+                Duration::from_millis(args.history_flush_ms),
+            )
+        });
 
         // Configure site struct.
         Ok(SiteWrapped {
-            db: Arc::new(Database::new("site.db").await?),
-            templater: Arc::new(create_templater()?),
-            pages: Arc::new(Pages::new(packed_data.clone())?),
-            theme_provider: Arc::new(ThemeProvider::new(packed_data.clone())?),
-            page_cache: Cache::new(args.cache_timeout),
-            packed_data,
+            db,
+            history,
+            agent_classifier: AgentClassifier::new(&packed_data),
+            junk_filter: JunkFilter::new(&args.junk_patterns)?,
+            templater: Reloadable::new(create_templater(&packed_data)?),
+            pages: Reloadable::new(pages),
+            theme_provider: Reloadable::new(ThemeProvider::new(packed_data.clone())?),
+            build_error: Reloadable::new(build_error),
+            // Always re-render in dev mode.
+            page_cache: Cache::new(match args.dev {
+                true => 0.0,
+                false => args.cache_timeout,
+            }),
+            packed_data: Reloadable::new(packed_data),
+            subscriber_estimate: Default::default(),
             config: args,
         })
     }
@@ -196,6 +390,9 @@ impl SiteWrapped {
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 pub struct SiteConfig {
+    /// Command to run, serving the site by default.
+    #[command(subcommand)]
+    pub command: Option<cli::Command>,
     /// Port to serve on.
     #[arg(short, long, value_name = "PORT", default_value_t = 8443)]
     pub port: u16,
@@ -211,9 +408,139 @@ pub struct SiteConfig {
     /// Debug logging.
     #[arg(short, long, default_value_t = false)]
     pub debug: bool,
+    /// Database path, or `:memory:` for a database that lives only as long as
+    /// the process.
+    #[arg(
+        long,
+        value_name = "DATABASE_PATH",
+        env = "HACHHA_DATABASE",
+        default_value = "site.db"
+    )]
+    pub database: String,
+    /// Directory database snapshots are written to.
+    #[arg(long, value_name = "BACKUP_DIR", default_value = "backups")]
+    pub backup_dir: PathBuf,
+    /// Number of database snapshots to keep (0 keeps them all).
+    #[arg(long, default_value_t = 7)]
+    pub backup_keep: usize,
+    /// How often the server snapshots the database (hours, 0 disables
+    /// scheduled snapshots).
+    #[arg(long, default_value_t = 0)]
+    pub backup_interval_hours: u64,
+    /// Don't record any analytics. The database is still opened and migrated
+    /// for the admin pages.
+    #[arg(long, default_value_t = false)]
+    pub no_analytics: bool,
+    /// Bearer token for the admin endpoints. Admin endpoints are disabled
+    /// without it.
+    #[arg(
+        long,
+        value_name = "TOKEN",
+        env = "HACHHA_ADMIN_TOKEN",
+        hide_env_values = true
+    )]
+    pub admin_token: Option<String>,
+    /// Secret that draft preview tokens are derived from. Drafts can't be
+    /// previewed without it, or when it's empty.
+    #[arg(
+        long,
+        value_name = "SECRET",
+        env = "HACHHA_DRAFT_SECRET",
+        hide_env_values = true
+    )]
+    pub draft_secret: Option<String>,
+    /// Maximum number of queued endpoint history events before new events
+    /// are dropped.
+    #[arg(long, default_value_t = 4096)]
+    pub history_queue_size: usize,
+    /// How often queued endpoint history events are written (milliseconds).
+    #[arg(long, default_value_t = 1000)]
+    pub history_flush_ms: u64,
+    /// Days of daily endpoint hits to keep before rolling them up by month.
+    #[arg(long, default_value_t = 90)]
+    pub daily_hits_retention_days: u32,
+    /// Months of monthly endpoint hits to keep (0 keeps them forever).
+    #[arg(long, default_value_t = 0)]
+    pub monthly_hits_retention_months: u32,
+    /// Days of referrer and user agent counts to keep (0 keeps them forever).
+    #[arg(long, default_value_t = 365)]
+    pub request_sources_retention_days: u32,
+    /// Days after which error endpoints with few hits are deleted (0 keeps
+    /// them forever).
+    #[arg(long, default_value_t = 30)]
+    pub junk_retention_days: u32,
+    /// Error endpoints with at most this many hits are deleted after
+    /// `--junk-retention-days`.
+    #[arg(long, default_value_t = 2)]
+    pub junk_max_hits: u32,
+    /// Regex of known-junk paths, whose 404s are counted together instead of
+    /// one endpoint each. Replaces the default patterns when given.
+    #[arg(
+        long = "junk-pattern",
+        value_name = "REGEX",
+        default_values_t = analytics::DEFAULT_JUNK_PATTERNS.iter().map(|pattern| pattern.to_string())
+    )]
+    pub junk_patterns: Vec<String>,
+    /// How often database maintenance runs (minutes).
+    #[arg(long, default_value_t = 60)]
+    pub maintenance_interval_mins: u64,
+    /// Whether blog feed entries carry the full post or only its summary.
+    #[arg(long, value_enum, default_value_t = pages::feed::FeedContent::Full)]
+    pub feed_content: pages::feed::FeedContent,
+    /// Number of posts per page on the blog index and tag pages (0 shows
+    /// every post on one page).
+    #[arg(long, default_value_t = 10)]
+    pub blog_page_size: usize,
+    /// Dev mode: serve content and templates from the content directory,
+    /// rebuilding on changes.
+    #[arg(long, default_value_t = false)]
+    pub dev: bool,
+    /// Content directory (containing `resources/` and `content/`) for dev mode.
+    #[arg(long, value_name = "CONTENT_DIR", default_value = ".")]
+    pub content_dir: PathBuf,
 }
 
 impl SiteConfig {
+    /// Database path.
+    pub fn database(&self) -> &str {
+        &self.database
+    }
+
+    /// Analytics retention policy.
+    pub fn retention(&self) -> db::RetentionPolicy {
+        db::RetentionPolicy {
+            daily_hits_days: self.daily_hits_retention_days,
+            monthly_hits_months: self.monthly_hits_retention_months,
+            request_sources_days: self.request_sources_retention_days,
+            junk_days: self.junk_retention_days,
+            junk_max_hits: self.junk_max_hits,
+        }
+    }
+
+    /// Database snapshot policy.
+    pub fn backup(&self) -> db::BackupPolicy {
+        db::BackupPolicy {
+            dir: self.backup_dir.clone(),
+            keep: self.backup_keep,
+        }
+    }
+
+    /// How often periodic jobs run.
+    pub fn maintenance_interval(&self) -> Duration {
+        Duration::from_secs(self.maintenance_interval_mins.max(1) * 60)
+    }
+
+    /// Load site content, from the content directory in dev mode.
+    pub fn load_packed_data(&self) -> Result<PackedData> {
+        match self.dev {
+            true => {
+                tracing::info!("Dev mode: serving from {}", self.content_dir.display());
+                PackedData::from_directory(&self.content_dir)
+            }
+            false => PackedData::new(self.packed_data()),
+        }
+    }
+
     fn packed_data(&self) -> PathBuf {
         match &self.packed_data {
             Some(path) => path.clone(),
@@ -222,70 +549,81 @@ impl SiteConfig {
     }
 }
 
+/// Resolve when the process is asked to stop.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Unable to listen for ctrl-c: {e}");
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Unable to listen for SIGTERM: {e}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
 #[derive(RustEmbed)]
 #[folder = "resources/templates/"]
 #[include = "*.html"]
 pub struct Templates;
 
-/// Create handlebars templater for the site.
-fn create_templater<'a>() -> Result<Handlebars<'a>> {
+/// Create handlebars templater for the site. Live packed data (dev mode)
+/// provides its own partials instead of the ones built into the binary.
+fn create_templater<'a>(packed_data: &PackedData) -> Result<Handlebars<'a>> {
     use handlebars::handlebars_helper;
 
     let mut templater = Handlebars::new();
 
     // Register templates.
-    for item in Templates::iter() {
-        let raw_template = match Templates::get(&item) {
-            Some(raw) => raw,
-            None => bail!("Unabel to get template for templater: {item:?}"),
-        };
-        let template = std::str::from_utf8(&raw_template.data)?;
-        let template_name: String = item.to_string(); //.strip_prefix("templates/").expect("...").to_owned();
+    let mut register = |template_name: &str, template: &[u8]| -> Result<()> {
+        let template = std::str::from_utf8(template)?;
         let res = templater.register_partial(&format!("templates/{}", template_name), template);
         if res.is_err() {
             tracing::error!("Unable to register partial {}: {:?}", template_name, res);
         }
+        Ok(())
+    };
+    match packed_data.is_live() {
+        true => {
+            for (path, data) in packed_data.iter() {
+                let Some(template_name) = path.strip_prefix("resources/templates/") else {
+                    continue;
+                };
+                if template_name.ends_with(".html") {
+                    register(template_name, data)?;
+                }
+            }
+        }
+        false => {
+            for item in Templates::iter() {
+                let raw_template = match Templates::get(&item) {
+                    Some(raw) => raw,
+                    None => bail!("Unabel to get template for templater: {item:?}"),
+                };
+                register(&item, &raw_template.data)?;
+            }
+        }
     }
 
     // Compile markdown to html.
     handlebars_helper!(markdown_helper: |content: String| {
-        let mut options = comrak::Options::default();
-        options.extension.strikethrough = true;
-        options.extension.table = true;
-        options.extension.tasklist = true;
-        options.extension.autolink = true;
-        options.extension.header_id_prefix_in_href = true;
-        options.extension.header_id_prefix = Some("".into());
-        options.extension.footnotes = true;
-        options.extension.inline_footnotes = true;
-        options.extension.alerts = true;
-        options.extension.shortcodes = true;
-        options.extension.wikilinks_title_before_pipe = true;
-        options.extension.underline = true;
-        options.extension.subscript = true;
-        options.extension.superscript = true;
-        options.extension.spoiler = true;
-        options.extension.greentext = true;
-        options.extension.cjk_friendly_emphasis = true;
-        options.extension.subtext = true;
-        options.extension.highlight = true;
-
-        options.render.gfm_quirks = true;
-        options.render.github_pre_lang = true;
-        options.render.r#unsafe = true;
-        // options.extension.phoenix_heex = true;
-
-        // FUTURE: Look at HeadingAdapter to make clickable.
-
-        let mut as_html = comrak::markdown_to_html(&content, &options);
-
-        if let Ok(icon_re) = regex::Regex::new(r"\{\{\s*icon\s*(?P<icon>\S+)\s*\}\}") {
-            as_html = icon_re.replace_all(&as_html, "<i class=\"ph-fill ph-$icon\"></i>").to_string();
-        } else {
-            tracing::error!("Failed to compile icon regex.");
-        }
-
-        as_html
+        util::markdown_to_html(&content)
     });
 
     // Allow html to be rendered normally (not escaped)-- dangerous, if used incorrectly.
@@ -311,3 +649,55 @@ fn create_templater<'a>() -> Result<Handlebars<'a>> {
 
     Ok(templater)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::pages::testing;
+    use crate::site::*;
+    use axum::response::IntoResponse;
+
+    /// Site in or out of dev mode with a broken post.
+    async fn site_with_broken_post(dev: bool) -> Site {
+        let mut args = vec!["hachha-dev", "--database", ":memory:", "--no-analytics"];
+        if dev {
+            args.push("--dev");
+        }
+        let packed_data = testing::packed_data(&[
+            (
+                "content/posts/2025-01-01-good.md",
+                &testing::post("good", "2025-01-01"),
+            ),
+            ("content/posts/2025-01-02-broken.md", "+++\nname = \n+++\n"),
+        ]);
+        Site::with_packed_data(SiteConfig::parse_from(args), packed_data)
+            .await
+            .unwrap()
+    }
+
+    /// Render a trivial page, returning the html.
+    async fn render(site: &Site) -> String {
+        let response = site
+            .render_page("<p>{{text}}</p>", &json!({"text": "ok"}))
+            .into_response();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn scheduled_reload_with_broken_post() {
+        // Scheduled publishing rebuilds from the packed data already loaded.
+        let site = site_with_broken_post(false).await;
+        site.reload(site.packed_data().as_ref().clone()).await;
+        assert!(!site.pages().errors().is_empty());
+
+        // Outside dev mode the other pages keep being served.
+        assert_eq!(render(&site).await, "<p>ok</p>");
+
+        // Dev mode shows the error instead.
+        let site = site_with_broken_post(true).await;
+        site.reload(site.packed_data().as_ref().clone()).await;
+        assert!(render(&site).await.contains("Unable to build site"));
+    }
+}
@@ -31,6 +31,8 @@ impl axum::response::IntoResponse for EmbeddedData {
 #[derive(Clone)]
 pub struct PackedData {
     data: Arc<HashMap<String, EmbeddedData>>,
+    /// Read from a content directory rather than the bundled archive.
+    live: bool,
 }
 
 impl PackedData {
@@ -94,9 +96,148 @@ impl PackedData {
 
         Ok(Self {
             data: Arc::new(data),
+            live: false,
         })
     }
 
+    /// Read packed data from a content directory, mirroring the layout of the
+    /// bundled archive (`resources/` and `content/`).
+    pub fn from_directory(root: impl AsRef<std::path::Path>) -> Result<Self> {
+        let mut data = HashMap::new();
+        for path in Self::scan_directory(root.as_ref())?.into_keys() {
+            if let Some(file) = Self::read_file(root.as_ref(), &path) {
+                data.insert(path, file);
+            }
+        }
+
+        Ok(Self {
+            data: Arc::new(data),
+            live: true,
+        })
+    }
+
+    /// Create a copy of this packed data with changed files re-read from the
+    /// content directory and removed files dropped.
+    pub fn with_changes(
+        &self,
+        root: impl AsRef<std::path::Path>,
+        changed: &[String],
+        removed: &[String],
+    ) -> Self {
+        let mut data = (*self.data).clone();
+        for path in removed {
+            data.remove(path);
+        }
+        for path in changed {
+            if let Some(file) = Self::read_file(root.as_ref(), path) {
+                data.insert(path.clone(), file);
+            }
+        }
+
+        Self {
+            data: Arc::new(data),
+            live: self.live,
+        }
+    }
+
+    /// Packed data holding the given text files, for tests.
+    #[cfg(test)]
+    pub fn from_files(files: &[(&str, &str)], live: bool) -> Self {
+        let data = files
+            .iter()
+            .map(|(path, text)| {
+                let data = EmbeddedData(Cow::Owned(text.as_bytes().to_vec()));
+                (path.to_string(), data)
+            })
+            .collect();
+
+        Self {
+            data: Arc::new(data),
+            live,
+        }
+    }
+
+    /// Whether the data is read from a content directory (dev mode), in which
+    /// case its templates take precedence over the ones built into the binary.
+    pub fn is_live(&self) -> bool {
+        self.live
+    }
+
+    /// Find all packable files in a content directory, along with their
+    /// modification time and size.
+    pub fn scan_directory(root: &std::path::Path) -> Result<HashMap<String, (SystemTime, u64)>> {
+        let mut files = HashMap::new();
+        let mut pending: Vec<PathBuf> = PACKED_DIRECTORIES.iter().map(|d| root.join(d)).collect();
+        while let Some(dir) = pending.pop() {
+            let entries = match std::fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    if dir.parent() == Some(root) {
+                        bail!("Failed to read content directory {}: {e}", dir.display());
+                    }
+                    tracing::warn!("Failed to read directory {}: {e}", dir.display());
+                    continue;
+                }
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                // Follow symlinks, like the bundled archive does.
+                let metadata = match std::fs::metadata(&path) {
+                    Ok(metadata) => metadata,
+                    Err(e) => {
+                        tracing::warn!("Failed to stat {}: {e}", path.display());
+                        continue;
+                    }
+                };
+                if metadata.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+                let relative = match path.strip_prefix(root) {
+                    Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+                    Err(_) => continue,
+                };
+                let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                files.insert(relative, (modified, metadata.len()));
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Read a single file from a content directory.
+    fn read_file(root: &std::path::Path, path: &str) -> Option<EmbeddedData> {
+        match std::fs::read(root.join(path)) {
+            Ok(data) => Some(EmbeddedData(Cow::Owned(data))),
+            Err(e) => {
+                tracing::warn!("Failed to read file `{path}`: {e}");
+                None
+            }
+        }
+    }
+
+    /// Stable FNV-1a hash of every path and file, used to tell deployed
+    /// content apart.
+    pub fn content_hash(&self) -> String {
+        const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+        const FNV_PRIME: u64 = 0x100000001b3;
+
+        let mut paths: Vec<&String> = self.data.keys().collect();
+        paths.sort();
+        let mut hash = FNV_OFFSET;
+        for path in paths {
+            for byte in path
+                .bytes()
+                .chain([0])
+                .chain(self.data[path].iter().copied())
+            {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        format!("{hash:016x}")
+    }
+
     pub fn iter<'a>(&'a self) -> std::collections::hash_map::Iter<'a, String, EmbeddedData> {
         self.data.iter()
     }
@@ -108,17 +249,6 @@ impl PackedData {
             .trim_start_matches("./")
             .trim_start_matches("/");
 
-        // In debug, read from file each time.
-        if cfg!(debug_assertions) {
-            return match std::fs::read(path) {
-                Ok(data) => Ok(EmbeddedData(Cow::Owned(data))),
-                Err(e) => {
-                    tracing::warn!("Failed to read file `{path}` in debug: {e}");
-                    bail!("FileNotFound")
-                }
-            };
-        }
-
         if let Some(data) = self.data.get(path) {
             return Ok(data.clone());
         }
@@ -147,6 +277,34 @@ impl PackedData {
     }
 }
 
+/// Directories included in packed data.
+const PACKED_DIRECTORIES: [&str; 2] = ["resources", "content"];
+
+/// Shared value that can be replaced while the site is running.
+pub struct Reloadable<T>(std::sync::RwLock<Arc<T>>);
+
+impl<T> Reloadable<T> {
+    pub fn new(value: impl Into<Arc<T>>) -> Self {
+        Self(std::sync::RwLock::new(value.into()))
+    }
+
+    /// Get the current value.
+    pub fn get(&self) -> Arc<T> {
+        match self.0.read() {
+            Ok(value) => value.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    /// Replace the current value.
+    pub fn set(&self, value: impl Into<Arc<T>>) {
+        match self.0.write() {
+            Ok(mut current) => *current = value.into(),
+            Err(poisoned) => *poisoned.into_inner() = value.into(),
+        }
+    }
+}
+
 /// Rendered html type.
 #[derive(Clone)]
 pub struct RenderedHtml(Arc<Html<String>>);
@@ -157,6 +315,12 @@ impl RenderedHtml {
     }
 }
 
+impl CacheWeight for RenderedHtml {
+    fn weight(&self) -> usize {
+        self.0 .0.len()
+    }
+}
+
 impl axum::response::IntoResponse for RenderedHtml {
     fn into_response(self) -> axum::response::Response {
         (*self.0).clone().into_response()
@@ -237,6 +401,105 @@ pub fn to_json<T: Serialize>(value: &T) -> Result<serde_json::Value> {
     Ok(serde_json::to_value(value)?)
 }
 
+/// Compile markdown to html, expanding `{{ icon name }}` shortcodes.
+pub fn markdown_to_html(content: &str) -> String {
+    let mut options = comrak::Options::default();
+    options.extension.strikethrough = true;
+    options.extension.table = true;
+    options.extension.tasklist = true;
+    options.extension.autolink = true;
+    options.extension.header_id_prefix_in_href = true;
+    options.extension.header_id_prefix = Some("".into());
+    options.extension.footnotes = true;
+    options.extension.inline_footnotes = true;
+    options.extension.alerts = true;
+    options.extension.shortcodes = true;
+    options.extension.wikilinks_title_before_pipe = true;
+    options.extension.underline = true;
+    options.extension.subscript = true;
+    options.extension.superscript = true;
+    options.extension.spoiler = true;
+    options.extension.greentext = true;
+    options.extension.cjk_friendly_emphasis = true;
+    options.extension.subtext = true;
+    options.extension.highlight = true;
+
+    options.render.gfm_quirks = true;
+    options.render.github_pre_lang = true;
+    options.render.r#unsafe = true;
+    // options.extension.phoenix_heex = true;
+
+    // FUTURE: Look at HeadingAdapter to make clickable.
+
+    let mut as_html = comrak::markdown_to_html(content, &options);
+
+    if let Ok(icon_re) = regex::Regex::new(r"\{\{\s*icon\s*(?P<icon>\S+)\s*\}\}") {
+        as_html = icon_re
+            .replace_all(&as_html, "<i class=\"ph-fill ph-$icon\"></i>")
+            .to_string();
+    } else {
+        tracing::error!("Failed to compile icon regex.");
+    }
+
+    as_html
+}
+
+/// Rewrite root-relative `href` and `src` urls in html to absolute urls on
+/// `origin`, for html read outside the site (e.g., in feed readers).
+pub fn absolute_urls(html: &str, origin: &str) -> String {
+    match regex::Regex::new(r#"(?P<attr>\b(?:href|src))="/(?P<rest>[^/"][^"]*)?""#) {
+        Ok(url_re) => url_re
+            .replace_all(html, format!(r#"$attr="{origin}/$rest""#))
+            .to_string(),
+        Err(e) => {
+            tracing::error!("Failed to compile url regex: {e}");
+            html.to_string()
+        }
+    }
+}
+
+/// Compare secrets without short-circuiting on the first mismatch.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter()
+        .zip(b.iter())
+        .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+        == 0
+}
+
+/// Hex encoded HMAC-SHA256 of `message`, keyed by `secret`.
+pub fn hmac_hex(secret: &str, message: &str) -> Option<String> {
+    use hmac::Mac;
+
+    let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(secret.as_bytes()).ok()?;
+    mac.update(message.as_bytes());
+    Some(
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect(),
+    )
+}
+
+/// Levenshtein distance between two strings, in characters.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            current[j + 1] = (previous[j] + usize::from(a_char != *b_char))
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
 /// Merge json values.
 pub fn merge_json(a: &mut serde_json::Value, b: &serde_json::Value) -> Result<()> {
     match (a, &b) {
@@ -297,3 +560,38 @@ pub fn adjust_content_header(
     };
     ([(axum::http::header::CONTENT_TYPE, content_type)], response)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::util::*;
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("scrapers", "scraper"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        // Distances count characters, not bytes.
+        assert_eq!(edit_distance("café", "cafe"), 1);
+        assert_eq!(edit_distance("日本語", "日本"), 1);
+        assert_eq!(edit_distance("🦀", "🐍"), 1);
+    }
+
+    #[test]
+    fn absolute_feed_urls() {
+        let html = concat!(
+            r#"<img src="/blog/media/a.png"><a href="/blog/b">b</a><a href="/">home</a>"#,
+            r#"<a href="//cdn.example.com/c">c</a><a href="https://example.com/d">d</a>"#,
+            r##"<a href="#note">note</a>"##,
+        );
+        assert_eq!(
+            absolute_urls(html, "https://hachha.dev"),
+            concat!(
+                r#"<img src="https://hachha.dev/blog/media/a.png"><a href="https://hachha.dev/blog/b">b</a><a href="https://hachha.dev/">home</a>"#,
+                r#"<a href="//cdn.example.com/c">c</a><a href="https://example.com/d">d</a>"#,
+                r##"<a href="#note">note</a>"##,
+            )
+        );
+    }
+}